@@ -1,17 +1,69 @@
 //! Audit checks for instruction files.
 //!
-//! Cross-cutting checks (check_context_invariant, check_staleness, check_line_budget)
-//! are re-exported from `agent-kit::audit_common`. Domain-specific checks
+//! Cross-cutting checks (check_context_invariant, check_staleness) are
+//! re-exported from `agent-kit::audit_common`. Domain-specific checks
 //! (check_actionable, check_tree_paths) are re-exported from `agent-rules`.
+//! check_line_budget wraps the `agent-kit` version to also count `@` imports.
 
-pub use agent_kit::audit_common::{check_context_invariant, check_line_budget, check_staleness};
+pub use agent_kit::audit_common::{check_context_invariant, check_staleness};
 pub use agent_rules::{check_actionable, check_tree_paths};
 
+use crate::imports::import_closure;
+use crate::types::{AuditConfig, Issue, is_agent_file};
+use agent_kit::audit_common::LINE_BUDGET;
+use std::path::{Path, PathBuf};
+
+/// Check combined line count against budget.
+///
+/// Only counts agent instruction files (AGENTS.md, SKILL.md, optionally CLAUDE.md),
+/// plus every file they transitively pull in through `@path` imports, since
+/// those are loaded into context too. Reference docs (README.md, SPEC.md) are
+/// listed but excluded from the budget.
+pub fn check_line_budget(
+    files: &[PathBuf],
+    root: &Path,
+    config: &AuditConfig,
+) -> (Vec<Issue>, Vec<(String, usize)>, usize) {
+    let (_, mut counts, mut total) =
+        agent_kit::audit_common::check_line_budget(files, root, config);
+
+    let agent_files: Vec<PathBuf> = files
+        .iter()
+        .filter(|f| is_agent_file(&f.strip_prefix(root).unwrap_or(f).to_string_lossy(), config))
+        .cloned()
+        .collect();
+    for imported in import_closure(&agent_files, root) {
+        if files.contains(&imported) {
+            continue;
+        }
+        if let Ok(content) = std::fs::read_to_string(&imported) {
+            let n = content.lines().count();
+            let rel = imported
+                .strip_prefix(root)
+                .unwrap_or(&imported)
+                .to_string_lossy();
+            total += n;
+            counts.push((format!("{} (imported)", rel), n));
+        }
+    }
+
+    let mut issues = Vec::new();
+    if total > LINE_BUDGET {
+        issues.push(Issue {
+            file: "(all)".to_string(),
+            line: 0,
+            end_line: 0,
+            message: format!("Over line budget: {} lines (max {})", total, LINE_BUDGET),
+            warning: false,
+        });
+    }
+    (issues, counts, total)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use agent_rules::extract_tree_paths;
-    use crate::types::AuditConfig;
     use std::fs;
     use tempfile::TempDir;
 
@@ -353,4 +405,39 @@ src/
         let issues = check_actionable("CLAUDE.md", content, &config);
         assert!(issues.is_empty()); // CLAUDE.md is not an agent file in corky config
     }
+
+    // --- check_line_budget ---
+
+    #[test]
+    fn check_line_budget_counts_imports() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("CLAUDE.md"), "# Doc\n@docs/extra.md\n").unwrap();
+        fs::write(root.join("README.md"), "@docs/readme-only.md\n").unwrap();
+        fs::write(root.join("docs/extra.md"), "a\nb\nc\n").unwrap();
+        fs::write(root.join("docs/readme-only.md"), "x\n").unwrap();
+
+        let config = AuditConfig::agent_doc();
+        let files = vec![root.join("CLAUDE.md"), root.join("README.md")];
+        let (issues, counts, total) = check_line_budget(&files, root, &config);
+        assert!(issues.is_empty());
+        assert_eq!(total, 5);
+        assert!(counts.contains(&("docs/extra.md (imported)".to_string(), 3)));
+        assert!(!counts.iter().any(|(name, _)| name.contains("readme-only")));
+    }
+
+    #[test]
+    fn check_line_budget_over_budget_via_imports() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("AGENTS.md"), "@big.md\n").unwrap();
+        fs::write(root.join("big.md"), "line\n".repeat(1000)).unwrap();
+
+        let config = AuditConfig::agent_doc();
+        let (issues, _, total) = check_line_budget(&[root.join("AGENTS.md")], root, &config);
+        assert_eq!(total, 1001);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("Over line budget"));
+    }
 }
@@ -0,0 +1,468 @@
+//! Task runner command validation for instruction files.
+//!
+//! Finds `make`, `just`, `npm`/`yarn`/`pnpm`/`bun run` and `cargo`
+//! invocations in inline code and shell code blocks, and checks that the
+//! target, recipe, script or subcommand actually exists in the project.
+
+use crate::markdown::{code_blocks, code_spans, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Shell fence languages whose lines are treated as commands.
+pub(crate) const SHELL_LANGS: &[&str] = &["sh", "bash", "shell", "zsh", "console", "shell-session"];
+
+#[rustfmt::skip]
+const CARGO_BUILTINS: &[&str] = &[
+    "add", "b", "bench", "build", "c", "check", "clean", "clippy", "config", "d", "doc", "fetch",
+    "fix", "fmt", "generate-lockfile", "help", "info", "init", "install", "locate-project",
+    "login", "logout", "metadata", "miri", "new", "owner", "package", "pkgid", "publish", "r",
+    "read-manifest", "remove", "report", "rm", "run", "rustc", "rustdoc", "search", "t", "test",
+    "tree", "uninstall", "update", "vendor", "verify-project", "version", "yank",
+];
+
+static MAKE_TARGET_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Za-z0-9_./%-][A-Za-z0-9_./% -]*?)\s*::?(?:[^=]|$)").unwrap());
+static MAKE_INCLUDE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-?include\s+(.+)$").unwrap());
+static JUST_RECIPE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@?([A-Za-z_][A-Za-z0-9_-]*)(?:\s[^:=]*)?:(?:[^=]|$)").unwrap());
+static JUST_ALIAS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^alias\s+([A-Za-z_][A-Za-z0-9_-]*)\s*:=").unwrap());
+
+/// A task runner invocation and the name it needs to resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Task {
+    /// `make [target]` (`None` = default target).
+    Make(Option<String>),
+    Just(Option<String>),
+    /// `npm run <script>`, `yarn run <script>`, ...
+    Script(String),
+    Cargo(String),
+}
+
+/// Split a shell line into simple commands on `&&`, `||`, `;` and `|`.
+pub(crate) fn split_commands(line: &str) -> Vec<&str> {
+    line.split("&&")
+        .flat_map(|s| s.split("||"))
+        .flat_map(|s| s.split(';'))
+        .flat_map(|s| s.split('|'))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Tokens that are placeholders in docs rather than real names.
+fn is_placeholder(token: &str) -> bool {
+    token.contains(['<', '>', '{', '}', '$', '[', ']']) || token.contains("...")
+}
+
+fn parse_task(command: &str) -> Option<Task> {
+    let mut tokens = command
+        .split_whitespace()
+        .skip_while(|t| t.contains('=') && !t.starts_with('-'));
+    let tool = tokens.next()?;
+    let args: Vec<&str> = tokens.collect();
+    if args.iter().any(|a| is_placeholder(a)) {
+        return None;
+    }
+    let positional = |args: &[&str]| {
+        args.iter()
+            .find(|a| !a.starts_with('-') && !a.contains('='))
+            .map(|a| a.to_string())
+    };
+    match tool {
+        "make" => {
+            // A different directory or makefile can't be resolved statically.
+            if args
+                .iter()
+                .any(|a| matches!(*a, "-C" | "-f" | "--directory" | "--file" | "--makefile"))
+            {
+                return None;
+            }
+            Some(Task::Make(positional(&args)))
+        }
+        "just" => {
+            if args.iter().any(|a| a.starts_with('-')) {
+                return None;
+            }
+            Some(Task::Just(positional(&args)))
+        }
+        "npm" => match args.first().copied() {
+            Some("run" | "run-script") => positional(&args[1..]).map(Task::Script),
+            Some("test" | "t" | "start" | "stop" | "restart") => {
+                let script = if args[0] == "t" { "test" } else { args[0] };
+                Some(Task::Script(script.to_string()))
+            }
+            _ => None,
+        },
+        "yarn" | "pnpm" | "bun" => match args.first().copied() {
+            Some("run") => positional(&args[1..]).map(Task::Script),
+            _ => None,
+        },
+        "cargo" => {
+            let sub = args
+                .iter()
+                .find(|a| !a.starts_with('-') && !a.starts_with('+'))?;
+            Some(Task::Cargo(sub.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Extract task runner commands from inline code and shell code blocks.
+///
+/// Returns `(line_number, command)` pairs. A bare tool name in inline code
+/// (`` `make` ``) is a mention, not a command, and is skipped. In `console`
+/// blocks only lines with a `$ ` prompt are considered; the prompt is stripped.
+pub fn extract_commands(content: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut push = |line: usize, text: &str| {
+        for cmd in split_commands(text) {
+            if parse_task(cmd).is_some() {
+                out.push((line, cmd.to_string()));
+            }
+        }
+    };
+    for (line, text) in prose_lines(content) {
+        for span in code_spans(text) {
+            let span = span.strip_prefix("$ ").unwrap_or(&span);
+            if span.contains(char::is_whitespace) {
+                push(line, span);
+            }
+        }
+    }
+    for block in code_blocks(content) {
+        let lang = block.lang();
+        if !SHELL_LANGS.contains(&lang.as_str()) {
+            continue;
+        }
+        let prompted = lang == "console" || lang == "shell-session";
+        for (line, text) in &block.lines {
+            let text = text.trim_start();
+            let text = match text.strip_prefix("$ ") {
+                Some(rest) => rest,
+                None if prompted => continue,
+                None => text,
+            };
+            if !text.starts_with('#') {
+                push(*line, text);
+            }
+        }
+    }
+    out.sort_by_key(|(line, _)| *line);
+    out
+}
+
+/// Find the first of `names` in `start` or an ancestor, stopping at `root`.
+pub(crate) fn find_up(root: &Path, start: &Path, names: &[&str]) -> Option<PathBuf> {
+    let mut dir = start.to_path_buf();
+    loop {
+        for name in names {
+            let candidate = root.join(&dir).join(name);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+fn makefile_targets(path: &Path, out: &mut Vec<String>, depth: usize) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return;
+    };
+    for line in content.lines() {
+        if line.starts_with(['\t', ' ', '#']) {
+            continue;
+        }
+        if let Some(cap) = MAKE_INCLUDE_RE.captures(line) {
+            if depth < 3 {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                for inc in cap[1].split_whitespace().filter(|i| !i.contains('$')) {
+                    makefile_targets(&dir.join(inc), out, depth + 1);
+                }
+            }
+            continue;
+        }
+        if line.contains(":=") || line.contains("?=") || line.contains("+=") {
+            continue;
+        }
+        if let Some(cap) = MAKE_TARGET_RE.captures(line) {
+            out.extend(cap[1].split_whitespace().map(str::to_string));
+        }
+    }
+}
+
+fn justfile_recipes(path: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            JUST_ALIAS_RE
+                .captures(line)
+                .or_else(|| JUST_RECIPE_RE.captures(line))
+                .map(|c| c[1].to_string())
+        })
+        .collect()
+}
+
+fn package_scripts(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .and_then(|v| {
+            v.get("scripts")?
+                .as_object()
+                .map(|m| m.keys().cloned().collect())
+        })
+        .unwrap_or_default()
+}
+
+fn cargo_aliases(root: &Path, start: &Path) -> Vec<String> {
+    let Some(path) = find_up(root, start, &[".cargo/config.toml", ".cargo/config"]) else {
+        return Vec::new();
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|c| c.parse::<toml::Table>().ok())
+        .and_then(|t| {
+            t.get("alias")?
+                .as_table()
+                .map(|a| a.keys().cloned().collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Check that task runner commands referenced in an instruction file exist.
+///
+/// Makefiles, justfiles, `package.json` and `.cargo/config.toml` are looked
+/// up from the instruction file's directory upwards to the project root.
+/// Cargo subcommands resolve against built-ins and `[alias]` entries; any
+/// other subcommand needs a `cargo-*` plugin and is only a warning, since
+/// whether one is installed depends on the machine.
+pub fn check_commands(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let dir = Path::new(rel)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+    let mut cache: HashMap<&'static str, Option<(String, Vec<String>)>> = HashMap::new();
+    let mut load = |kind: &'static str| -> Option<(String, Vec<String>)> {
+        cache
+            .entry(kind)
+            .or_insert_with(|| {
+                let names: &[&str] = match kind {
+                    "make" => &["GNUmakefile", "makefile", "Makefile"],
+                    "just" => &["justfile", "Justfile", ".justfile"],
+                    _ => &["package.json"],
+                };
+                let path = find_up(root, &dir, names)?;
+                let names = match kind {
+                    "make" => {
+                        let mut targets = Vec::new();
+                        makefile_targets(&path, &mut targets, 0);
+                        targets
+                    }
+                    "just" => justfile_recipes(&path),
+                    _ => package_scripts(&path),
+                };
+                let shown = path.strip_prefix(root).unwrap_or(&path);
+                Some((shown.to_string_lossy().to_string(), names))
+            })
+            .clone()
+    };
+
+    let mut issues = Vec::new();
+    for (line, command) in extract_commands(content) {
+        let Some(task) = parse_task(&command) else {
+            continue;
+        };
+        let problem = match &task {
+            Task::Make(target) | Task::Just(target) => {
+                let (kind, file, noun) = match task {
+                    Task::Make(_) => ("make", "Makefile", "target"),
+                    _ => ("just", "justfile", "recipe"),
+                };
+                match (load(kind), target) {
+                    (None, _) => Some(format!("no {} found", file)),
+                    (Some((path, names)), Some(t)) if !names.contains(t) => {
+                        Some(format!("no `{}` {} in {}", t, noun, path))
+                    }
+                    _ => None,
+                }
+            }
+            Task::Script(script) => match load("npm") {
+                None => Some("no package.json found".to_string()),
+                Some((path, names)) if !names.contains(script) => {
+                    Some(format!("no `{}` script in {}", script, path))
+                }
+                _ => None,
+            },
+            Task::Cargo(sub) => {
+                let known = CARGO_BUILTINS.contains(&sub.as_str())
+                    || cargo_aliases(root, &dir).contains(sub);
+                (!known).then(|| {
+                    format!(
+                        "not a built-in cargo subcommand or alias; needs the `cargo-{}` plugin",
+                        sub
+                    )
+                })
+            }
+        };
+        if let Some(problem) = problem {
+            issues.push(Issue {
+                file: rel.to_string(),
+                line,
+                end_line: 0,
+                message: format!("Command `{}` \u{2014} {}", command, problem),
+                warning: matches!(task, Task::Cargo(_)),
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn extract_commands_from_spans_and_shell_blocks() {
+        let content = "\
+Run `make precommit` then `npm run lint`, not `make`.
+
+```sh
+cargo build && make test
+# make commented
+```
+
+```console
+$ just fmt
+make output-not-a-command
+```
+
+```text
+make ignored
+```
+";
+        let cmds = extract_commands(content);
+        assert_eq!(
+            cmds,
+            vec![
+                (1, "make precommit".to_string()),
+                (1, "npm run lint".to_string()),
+                (4, "cargo build".to_string()),
+                (4, "make test".to_string()),
+                (9, "just fmt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_task_skips_placeholders_and_directories() {
+        assert_eq!(parse_task("make <target>"), None);
+        assert_eq!(parse_task("make -C sub build"), None);
+        assert_eq!(
+            parse_task("CI=1 make -j4 check"),
+            Some(Task::Make(Some("check".to_string())))
+        );
+        assert_eq!(parse_task("npm t"), Some(Task::Script("test".to_string())));
+        assert_eq!(
+            parse_task("cargo +nightly -q udeps"),
+            Some(Task::Cargo("udeps".to_string()))
+        );
+    }
+
+    #[test]
+    fn missing_makefile_reported() {
+        let tmp = TempDir::new().unwrap();
+        let issues = check_commands("DEV.md", "Run `make precommit`.\n", tmp.path());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].message,
+            "Command `make precommit` \u{2014} no Makefile found"
+        );
+        assert!(!issues[0].warning);
+    }
+
+    #[test]
+    fn make_and_just_targets_resolved() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Makefile"),
+            "VERSION := 1\ninclude extra.mk\n.PHONY: check\ncheck test: build\n\tcargo test\n",
+        )
+        .unwrap();
+        fs::write(root.join("extra.mk"), "precommit:\n\t@true\n").unwrap();
+        fs::write(
+            root.join("justfile"),
+            "alias f := fmt\nfmt *args:\n    cargo fmt\n",
+        )
+        .unwrap();
+
+        let content = "`make check` `make test` `make precommit` `make release` `just f` `just fmt` `just lint`\n";
+        let issues = check_commands("AGENTS.md", content, root);
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Command `make release` \u{2014} no `release` target in Makefile",
+                "Command `just lint` \u{2014} no `lint` recipe in justfile",
+            ]
+        );
+    }
+
+    #[test]
+    fn npm_scripts_resolved_from_nearest_package_json() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"build": "tsc"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("web/package.json"),
+            r#"{"scripts": {"lint": "eslint ."}}"#,
+        )
+        .unwrap();
+
+        let issues = check_commands("web/AGENTS.md", "`npm run lint` `npm run build`\n", root);
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0]
+                .message
+                .contains("no `build` script in web/package.json")
+        );
+    }
+
+    #[test]
+    fn cargo_subcommands_and_aliases() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".cargo")).unwrap();
+        fs::write(
+            root.join(".cargo/config.toml"),
+            "[alias]\nxtask = \"run -p xtask --\"\n",
+        )
+        .unwrap();
+
+        let content = "`cargo clippy` `cargo xtask dist` `cargo nextest run`\n";
+        let issues = check_commands("AGENTS.md", content, root);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].message,
+            "Command `cargo nextest run` \u{2014} not a built-in cargo subcommand or alias; needs the `cargo-nextest` plugin"
+        );
+        assert!(issues[0].warning);
+    }
+}
@@ -0,0 +1,245 @@
+//! Project-level configuration loaded from `instruction-files.toml`.
+//!
+//! Discovery settings live in [`AuditConfig`](crate::AuditConfig); this file
+//! holds per-project settings for the optional checks. A missing file means
+//! all defaults.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Config file name, looked up at the project root.
+pub const CONFIG_FILE: &str = "instruction-files.toml";
+
+/// Per-project settings from `instruction-files.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub urls: UrlConfig,
+    pub symbols: SymbolConfig,
+    pub verify: VerifyConfig,
+    pub security: SecurityConfig,
+    pub secrets: SecretsConfig,
+    pub ontology: OntologyConfig,
+    pub runbooks: RunbookConfig,
+}
+
+impl ProjectConfig {
+    /// Load `instruction-files.toml` from `root`, or defaults if it doesn't exist.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        for rule in &config.security.rules {
+            regex::Regex::new(&rule.pattern).with_context(|| {
+                format!(
+                    "invalid pattern for security rule `{}` in {}",
+                    rule.id,
+                    path.display()
+                )
+            })?;
+        }
+        for rule in &config.secrets.rules {
+            regex::Regex::new(&rule.pattern).with_context(|| {
+                format!(
+                    "invalid pattern for secrets rule `{}` in {}",
+                    rule.id,
+                    path.display()
+                )
+            })?;
+        }
+        Ok(config)
+    }
+}
+
+/// `[urls]` — external link checking (`audit --check-urls`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UrlConfig {
+    /// If non-empty, only URLs matching one of these patterns are checked.
+    /// A pattern is a host (`docs.rs`, `*.github.com`) or a URL prefix.
+    pub allow: Vec<String>,
+    /// URLs matching any of these patterns are never checked.
+    pub deny: Vec<String>,
+    /// Maximum number of requests in flight.
+    pub concurrency: usize,
+    /// Minimum delay between requests to the same host, in milliseconds.
+    pub per_domain_delay_ms: u64,
+    /// Request timeout, in seconds.
+    pub timeout_secs: u64,
+    /// How long a cached result stays valid, in hours.
+    pub cache_ttl_hours: u64,
+}
+
+impl Default for UrlConfig {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            concurrency: 8,
+            per_domain_delay_ms: 500,
+            timeout_secs: 10,
+            cache_ttl_hours: 24,
+        }
+    }
+}
+
+/// `[symbols]` — backticked symbol references checked against the source tree.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SymbolConfig {
+    /// Identifiers that are never reported (e.g. items from dependencies).
+    pub ignore: Vec<String>,
+}
+
+/// `[verify]` — `verify-snippets` sandbox settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerifyConfig {
+    /// Default per-block timeout, in seconds (override with `timeout=N`).
+    pub timeout_secs: u64,
+    /// Environment variables passed through to snippets besides `PATH`.
+    pub pass_env: Vec<String>,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 60,
+            pass_env: Vec::new(),
+        }
+    }
+}
+
+/// `[security]` — dangerous-instruction rules.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// Rule IDs (built-in or custom) whose findings are accepted.
+    pub allow: Vec<String>,
+    /// Extra project-specific rules (`[[security.rules]]`).
+    pub rules: Vec<SecurityRule>,
+}
+
+/// A custom dangerous-instruction rule: a regex matched against every line.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecurityRule {
+    pub id: String,
+    pub pattern: String,
+    pub message: String,
+}
+
+/// `[secrets]` — credential leak detection.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretsConfig {
+    /// Rule IDs (built-in or custom) whose findings are accepted.
+    pub allow: Vec<String>,
+    /// Extra secret patterns (`[[secrets.rules]]`).
+    pub rules: Vec<SecretRule>,
+}
+
+/// A custom secret pattern. When the regex has a capture group, group 1 is
+/// the secret; otherwise the whole match is.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretRule {
+    pub id: String,
+    pub pattern: String,
+}
+
+/// `[ontology]` — ontology term checks (requires the `ontology` feature).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OntologyConfig {
+    /// Ontology directories, relative to the project root, in order of
+    /// precedence. Used when `--ontology-dir` isn't given; if both are
+    /// empty, an `existence.toml` manifest is looked for instead.
+    pub dirs: Vec<PathBuf>,
+    /// Most `[term:Name]` annotations `audit --fix` inserts per file per run.
+    pub max_annotations_per_file: usize,
+}
+
+impl Default for OntologyConfig {
+    fn default() -> Self {
+        Self {
+            dirs: Vec::new(),
+            max_annotations_per_file: 5,
+        }
+    }
+}
+
+/// `[runbooks]` — runbook structure checks and extraction.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunbookConfig {
+    /// `##` sections every runbook must have (e.g. `"Preconditions"`).
+    /// `"Steps"` is also satisfied by a `## Checklist` section.
+    pub required_sections: Vec<String>,
+    /// Minimum length, in lines, of a numbered-step section that
+    /// `extract-runbook` proposes moving to a runbook.
+    pub candidate_min_lines: usize,
+}
+
+impl Default for RunbookConfig {
+    fn default() -> Self {
+        Self {
+            required_sections: Vec::new(),
+            candidate_min_lines: 20,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_file_uses_defaults() {
+        let tmp = TempDir::new().unwrap();
+        let config = ProjectConfig::load(tmp.path()).unwrap();
+        assert_eq!(config.urls.concurrency, 8);
+        assert!(config.urls.allow.is_empty());
+    }
+
+    #[test]
+    fn partial_section_keeps_other_defaults() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join(CONFIG_FILE),
+            "[urls]\ndeny = [\"localhost\"]\nconcurrency = 2\n",
+        )
+        .unwrap();
+        let config = ProjectConfig::load(tmp.path()).unwrap();
+        assert_eq!(config.urls.deny, vec!["localhost"]);
+        assert_eq!(config.urls.concurrency, 2);
+        assert_eq!(config.urls.cache_ttl_hours, 24);
+    }
+
+    #[test]
+    fn unknown_key_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(CONFIG_FILE), "[urls]\nconcurency = 2\n").unwrap();
+        assert!(ProjectConfig::load(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn invalid_security_pattern_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join(CONFIG_FILE),
+            "[[security.rules]]\nid = \"x\"\npattern = \"(\"\nmessage = \"m\"\n",
+        )
+        .unwrap();
+        let err = ProjectConfig::load(tmp.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("security rule `x`"));
+    }
+}
@@ -0,0 +1,281 @@
+//! Effective instruction set for a source path.
+//!
+//! Answers "which instruction files will an agent load when it edits this
+//! file?" by walking the directory chain from the project root down to the
+//! file, following `@path` imports, and matching path-scoped rules in
+//! `.claude/rules/`.
+
+use crate::imports::local_imports;
+use crate::markdown::{parse_front_matter, strip_front_matter};
+use crate::types::AuditConfig;
+use anyhow::{Result, bail};
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Why a file is part of the effective instruction set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextSource {
+    /// AGENTS.md/CLAUDE.md at the project root.
+    Root,
+    /// AGENTS.md/CLAUDE.md in a directory between the root and the target.
+    Nested,
+    /// Pulled in by an `@path` import in another file.
+    Import { from: PathBuf },
+    /// Path-scoped rule whose `paths` glob matches the target.
+    Rule { pattern: String },
+}
+
+/// One file in the effective instruction set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextEntry {
+    /// Path relative to the project root.
+    pub path: PathBuf,
+    pub source: ContextSource,
+    /// Content as the agent sees it (front matter stripped from rules).
+    pub content: String,
+    pub lines: usize,
+    pub tokens: usize,
+}
+
+/// Rough token estimate (~4 characters per token).
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Convert a user-supplied path (absolute, or relative to CWD) into a path
+/// relative to `root`. Fails when the path is not under `root`.
+pub fn relative_to_root(root: &Path, path: &Path) -> Result<PathBuf> {
+    let root_abs = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let abs = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let abs = abs.canonicalize().unwrap_or(abs);
+    match abs.strip_prefix(&root_abs) {
+        Ok(rel) => Ok(rel.to_path_buf()),
+        Err(_) => bail!(
+            "{} is outside the project root {}",
+            path.display(),
+            root.display()
+        ),
+    }
+}
+
+/// Compute the ordered chain of instruction files that apply to `target`.
+///
+/// `target` is relative to `root`. The chain is:
+/// 1. Root `AGENTS.md`, then `CLAUDE.md`/`.claude/CLAUDE.md` (if `config.include_claude_md`)
+/// 2. `AGENTS.md`/`CLAUDE.md` in each directory from the root down to the target
+/// 3. Path-scoped rules in `.claude/rules/**/*.md` whose `paths` front matter
+///    matches `target` (rules without `paths` always apply)
+///
+/// Each file is immediately followed by its `@path` imports, depth-first.
+/// A file appears at most once, at its first position.
+pub fn effective_context(root: &Path, target: &Path, config: &AuditConfig) -> Vec<ContextEntry> {
+    let mut names = vec!["AGENTS.md"];
+    if config.include_claude_md {
+        names.push("CLAUDE.md");
+    }
+
+    let mut chain: Vec<(PathBuf, ContextSource)> = Vec::new();
+    let mut dir = PathBuf::new();
+    let mut dirs = vec![dir.clone()];
+    if let Some(parent) = target.parent() {
+        for comp in parent.components() {
+            dir.push(comp);
+            dirs.push(dir.clone());
+        }
+    }
+    for (depth, d) in dirs.iter().enumerate() {
+        let source = if depth == 0 {
+            ContextSource::Root
+        } else {
+            ContextSource::Nested
+        };
+        for name in &names {
+            chain.push((d.join(name), source.clone()));
+        }
+        if depth == 0 && config.include_claude_md {
+            chain.push((PathBuf::from(".claude/CLAUDE.md"), ContextSource::Root));
+        }
+    }
+    for (rule, pattern) in matching_rules(root, target) {
+        chain.push((rule, ContextSource::Rule { pattern }));
+    }
+
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    for (path, source) in chain {
+        if root.join(&path).is_file() {
+            push_with_imports(root, path, source, &mut seen, &mut entries);
+        }
+    }
+    entries
+}
+
+fn push_with_imports(
+    root: &Path,
+    path: PathBuf,
+    source: ContextSource,
+    seen: &mut HashSet<PathBuf>,
+    entries: &mut Vec<ContextEntry>,
+) {
+    if !seen.insert(path.clone()) {
+        return;
+    }
+    let Ok(raw) = std::fs::read_to_string(root.join(&path)) else {
+        return;
+    };
+    let content = match source {
+        ContextSource::Rule { .. } => strip_front_matter(&raw).to_string(),
+        _ => raw,
+    };
+    entries.push(ContextEntry {
+        path: path.clone(),
+        source,
+        lines: content.lines().count(),
+        tokens: estimate_tokens(&content),
+        content,
+    });
+    for imported in local_imports(&path, root) {
+        let source = ContextSource::Import { from: path.clone() };
+        push_with_imports(root, imported, source, seen, entries);
+    }
+}
+
+/// Rules under `.claude/rules/` that apply to `target`, with the matching
+/// pattern (`*` for rules without `paths`).
+fn matching_rules(root: &Path, target: &Path) -> Vec<(PathBuf, String)> {
+    let opts = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let pattern = root.join(".claude/rules/**/*.md");
+    let mut rules: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .map(|entries| entries.flatten().collect())
+        .unwrap_or_default();
+    rules.sort();
+
+    let mut out = Vec::new();
+    for rule in rules {
+        let Ok(content) = std::fs::read_to_string(&rule) else {
+            continue;
+        };
+        let rel = rule.strip_prefix(root).unwrap_or(&rule).to_path_buf();
+        let paths = parse_front_matter(&content)
+            .map(|fm| fm.list("paths"))
+            .unwrap_or_default();
+        if paths.is_empty() {
+            out.push((rel, "*".to_string()));
+            continue;
+        }
+        if let Some(p) = paths.iter().find(|p| {
+            Pattern::new(p)
+                .map(|pat| pat.matches_path_with(target, opts))
+                .unwrap_or(false)
+        }) {
+            out.push((rel, p.clone()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn paths(entries: &[ContextEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| e.path.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn chain_orders_root_nested_imports_and_rules() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src/foo")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::create_dir_all(root.join(".claude/rules")).unwrap();
+        fs::write(root.join("AGENTS.md"), "# Root\n").unwrap();
+        fs::write(root.join("CLAUDE.md"), "@docs/style.md\n").unwrap();
+        fs::write(root.join("docs/style.md"), "style\n").unwrap();
+        fs::write(root.join("src/foo/AGENTS.md"), "# Foo\n").unwrap();
+        fs::write(root.join("src/AGENTS.md"), "@../docs/style.md\n").unwrap();
+        fs::write(
+            root.join(".claude/rules/rust.md"),
+            "---\npaths: [\"src/**/*.rs\"]\n---\nUse anyhow.\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".claude/rules/python.md"),
+            "---\npaths: [\"**/*.py\"]\n---\nUse ruff.\n",
+        )
+        .unwrap();
+        fs::write(root.join(".claude/rules/always.md"), "Be brief.\n").unwrap();
+
+        let config = AuditConfig::agent_doc();
+        let entries = effective_context(root, Path::new("src/foo/bar.rs"), &config);
+        assert_eq!(
+            paths(&entries),
+            vec![
+                "AGENTS.md",
+                "CLAUDE.md",
+                "docs/style.md",
+                "src/AGENTS.md",
+                "src/foo/AGENTS.md",
+                ".claude/rules/always.md",
+                ".claude/rules/rust.md",
+            ]
+        );
+        assert_eq!(
+            entries[2].source,
+            ContextSource::Import {
+                from: PathBuf::from("CLAUDE.md")
+            }
+        );
+        assert_eq!(entries[4].source, ContextSource::Nested);
+        assert_eq!(entries[6].content, "Use anyhow.\n");
+        assert_eq!(entries[6].lines, 1);
+    }
+
+    #[test]
+    fn corky_config_skips_claude_md() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("AGENTS.md"), "# Root\n").unwrap();
+        fs::write(root.join("CLAUDE.md"), "# Claude\n").unwrap();
+
+        let config = AuditConfig::corky();
+        let entries = effective_context(root, Path::new("src/main.rs"), &config);
+        assert_eq!(paths(&entries), vec!["AGENTS.md"]);
+    }
+
+    #[test]
+    fn relative_to_root_rejects_outside_paths() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(tmp.path().join("other.rs"), "").unwrap();
+
+        assert_eq!(
+            relative_to_root(&root, &root.join("src/main.rs")).unwrap(),
+            PathBuf::from("src/main.rs")
+        );
+        let err = relative_to_root(&root, &tmp.path().join("other.rs")).unwrap_err();
+        assert!(
+            err.to_string().contains("is outside the project root"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn estimate_tokens_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
@@ -0,0 +1,473 @@
+//! Ontology export and glossary generation.
+//!
+//! Builds one entry per term (definition, aliases, `is_a` parents, related
+//! terms and the instruction files that annotate it) and renders the set
+//! as Markdown, JSON, JSON-LD, SKOS Turtle or Graphviz DOT. A compact
+//! Markdown glossary can be kept in an instruction file between
+//! [`GLOSSARY_START`] and [`GLOSSARY_END`] markers; the audit reports it
+//! when it drifts from the ontology.
+
+use crate::ontology::{TermIndex, definition, display_path, prose_annotations, related_terms};
+use crate::types::Issue;
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Marker opening a generated glossary section.
+pub const GLOSSARY_START: &str = "<!-- ontology-glossary:start -->";
+/// Marker closing a generated glossary section.
+pub const GLOSSARY_END: &str = "<!-- ontology-glossary:end -->";
+
+/// IRI prefix for terms in JSON-LD and SKOS output.
+const TERM_IRI: &str = "urn:ontology:";
+
+/// One term with everything the export formats need.
+#[derive(Debug, Clone, Serialize)]
+pub struct GlossaryEntry {
+    /// Canonical spelling (`billing/Invoice`).
+    pub name: String,
+    /// First definition paragraph, if any.
+    pub definition: Option<String>,
+    pub aliases: Vec<String>,
+    /// Canonical names of `is_a` parents that exist.
+    pub is_a: Vec<String>,
+    /// Canonical names of terms under `## Related`.
+    pub related: Vec<String>,
+    /// Term file, relative to the project root when inside it.
+    pub path: String,
+    /// Instruction files annotating the term, relative to the project root.
+    pub referenced_by: Vec<String>,
+}
+
+/// Output format for [`export_ontology`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    JsonLd,
+    Skos,
+    Dot,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            "jsonld" | "json-ld" => Ok(Self::JsonLd),
+            "skos" | "ttl" => Ok(Self::Skos),
+            "dot" => Ok(Self::Dot),
+            _ => Err(format!(
+                "unknown format `{}` (expected md, json, jsonld, skos or dot)",
+                s
+            )),
+        }
+    }
+}
+
+/// Build a glossary entry for every term in `index`, sorted by name.
+///
+/// References are found the same way as [`check_ontology_coverage`]: every
+/// `[term:Name]` annotation in the prose of `files`, aliases included.
+///
+/// [`check_ontology_coverage`]: crate::ontology::check_ontology_coverage
+pub fn build_glossary(root: &Path, files: &[PathBuf], index: &TermIndex) -> Vec<GlossaryEntry> {
+    let mut refs: Vec<(String, String)> = Vec::new();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let rel = display_path(file, root);
+        for (_, name) in prose_annotations(&content) {
+            if let Some((term, _)) = index.lookup(&name) {
+                refs.push((term.name.to_lowercase(), rel.clone()));
+            }
+        }
+    }
+
+    index
+        .terms()
+        .map(|term| {
+            let content = std::fs::read_to_string(&term.path).unwrap_or_default();
+            let key = term.name.to_lowercase();
+            let mut referenced_by: Vec<String> = refs
+                .iter()
+                .filter(|(k, _)| *k == key)
+                .map(|(_, f)| f.clone())
+                .collect();
+            referenced_by.sort();
+            referenced_by.dedup();
+            GlossaryEntry {
+                name: term.name.clone(),
+                definition: definition(&content),
+                aliases: term.aliases.clone(),
+                is_a: term
+                    .is_a
+                    .iter()
+                    .filter_map(|p| index.lookup(p).map(|(t, _)| t.name.clone()))
+                    .collect(),
+                related: related_terms(term, &content, index)
+                    .into_iter()
+                    .map(|t| t.name.clone())
+                    .collect(),
+                path: display_path(&term.path, root),
+                referenced_by,
+            }
+        })
+        .collect()
+}
+
+/// IRI for a term: the lowercased name, percent-encoded except for
+/// unreserved characters and the `/` namespace separator.
+fn iri(name: &str) -> String {
+    let mut out = String::from(TERM_IRI);
+    for b in name.to_lowercase().bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Escape a string for a Turtle or DOT double-quoted literal.
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Render `entries` in `format`.
+pub fn export_ontology(entries: &[GlossaryEntry], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => export_markdown(entries),
+        ExportFormat::Json => serde_json::to_string_pretty(entries).unwrap_or_default() + "\n",
+        ExportFormat::JsonLd => {
+            let graph: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|e| {
+                    json!({
+                        "@id": iri(&e.name),
+                        "@type": "skos:Concept",
+                        "skos:prefLabel": e.name,
+                        "skos:altLabel": e.aliases,
+                        "skos:definition": e.definition,
+                        "skos:broader": e.is_a.iter().map(|p| json!({"@id": iri(p)})).collect::<Vec<_>>(),
+                        "skos:related": e.related.iter().map(|r| json!({"@id": iri(r)})).collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            let doc = json!({
+                "@context": {"skos": "http://www.w3.org/2004/02/skos/core#"},
+                "@graph": graph,
+            });
+            serde_json::to_string_pretty(&doc).unwrap_or_default() + "\n"
+        }
+        ExportFormat::Skos => {
+            let mut out = String::from("@prefix skos: <http://www.w3.org/2004/02/skos/core#> .\n");
+            for e in entries {
+                out.push_str(&format!("\n<{}> a skos:Concept ;\n", iri(&e.name)));
+                out.push_str(&format!("    skos:prefLabel {}", quote(&e.name)));
+                for alias in &e.aliases {
+                    out.push_str(&format!(" ;\n    skos:altLabel {}", quote(alias)));
+                }
+                if let Some(def) = &e.definition {
+                    out.push_str(&format!(" ;\n    skos:definition {}", quote(def)));
+                }
+                for parent in &e.is_a {
+                    out.push_str(&format!(" ;\n    skos:broader <{}>", iri(parent)));
+                }
+                for related in &e.related {
+                    out.push_str(&format!(" ;\n    skos:related <{}>", iri(related)));
+                }
+                out.push_str(" .\n");
+            }
+            out
+        }
+        ExportFormat::Dot => {
+            let mut out = String::from("digraph ontology {\n    node [shape=box];\n");
+            for e in entries {
+                out.push_str(&format!("    {};\n", quote(&e.name)));
+            }
+            for e in entries {
+                for parent in &e.is_a {
+                    out.push_str(&format!(
+                        "    {} -> {} [label=\"is_a\"];\n",
+                        quote(&e.name),
+                        quote(parent)
+                    ));
+                }
+                for related in &e.related {
+                    out.push_str(&format!(
+                        "    {} -> {} [style=dashed];\n",
+                        quote(&e.name),
+                        quote(related)
+                    ));
+                }
+            }
+            out.push_str("}\n");
+            out
+        }
+    }
+}
+
+fn export_markdown(entries: &[GlossaryEntry]) -> String {
+    let mut out = String::from("# Glossary\n");
+    for e in entries {
+        out.push_str(&format!("\n## {}\n\n", e.name));
+        if let Some(def) = &e.definition {
+            out.push_str(&format!("{}\n\n", def));
+        }
+        let list = |items: &[String]| items.join(", ");
+        if !e.aliases.is_empty() {
+            out.push_str(&format!("- Aliases: {}\n", list(&e.aliases)));
+        }
+        if !e.is_a.is_empty() {
+            out.push_str(&format!("- Is a: {}\n", list(&e.is_a)));
+        }
+        if !e.related.is_empty() {
+            out.push_str(&format!("- Related: {}\n", list(&e.related)));
+        }
+        out.push_str(&format!("- Defined in: `{}`\n", e.path));
+        if !e.referenced_by.is_empty() {
+            let files: Vec<String> = e.referenced_by.iter().map(|f| format!("`{}`", f)).collect();
+            out.push_str(&format!("- Referenced by: {}\n", files.join(", ")));
+        }
+    }
+    out
+}
+
+/// The compact glossary kept between the markers: one bullet per term with
+/// its aliases and definition.
+pub fn render_glossary(entries: &[GlossaryEntry]) -> String {
+    let mut out = String::new();
+    for e in entries {
+        out.push_str(&format!("- **{}**", e.name));
+        if !e.aliases.is_empty() {
+            out.push_str(&format!(" ({})", e.aliases.join(", ")));
+        }
+        if let Some(def) = &e.definition {
+            out.push_str(&format!(": {}", def));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Byte range of the text between the glossary markers, and the 1-based
+/// line of the start marker.
+fn marker_range(content: &str) -> Option<(usize, usize, usize)> {
+    let start = content.find(GLOSSARY_START)?;
+    let body = content[start..]
+        .find('\n')
+        .map_or(content.len(), |i| start + i + 1);
+    let end = body + content[body..].find(GLOSSARY_END)?;
+    let line = content[..start].matches('\n').count() + 1;
+    Some((body, end, line))
+}
+
+/// 1-based lines of the start and end markers. Term mention checks skip
+/// this range: the glossary is regenerated, so annotating it won't stick.
+pub(crate) fn glossary_lines(content: &str) -> Option<(usize, usize)> {
+    let (_, end, line) = marker_range(content)?;
+    Some((line, content[..end].matches('\n').count() + 1))
+}
+
+/// Replace the text between the glossary markers with `glossary`.
+///
+/// `None` when `content` has no complete marker pair.
+pub fn inject_glossary(content: &str, glossary: &str) -> Option<String> {
+    let (body, end, _) = marker_range(content)?;
+    Some(format!(
+        "{}{}{}",
+        &content[..body],
+        glossary,
+        &content[end..]
+    ))
+}
+
+/// Report a glossary section that no longer matches `glossary`.
+pub fn check_glossary(rel: &str, content: &str, glossary: &str) -> Vec<Issue> {
+    let Some((body, end, line)) = marker_range(content) else {
+        return Vec::new();
+    };
+    if content[body..end] == *glossary {
+        return Vec::new();
+    }
+    vec![Issue {
+        file: rel.to_string(),
+        line,
+        end_line: 0,
+        message:
+            "Glossary is out of date with the ontology \u{2014} run `instruction-files ontology glossary`"
+                .to_string(),
+        warning: false,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::{annotate_terms, check_term_mentions};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("ontology/src/billing");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            tmp.path().join("ontology/src/entity.md"),
+            "# Entity\n\nSomething that \"exists\".\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("invoice.md"),
+            "---\naliases: [bill]\nis_a: [Entity]\n---\n# Invoice\n\nA request\nfor payment.\n\n## Related\n\n- [Entity](../entity.md)\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("AGENTS.md"),
+            "Send a [term:bill] per [term:Entity].\n",
+        )
+        .unwrap();
+        tmp
+    }
+
+    fn entries(tmp: &TempDir) -> Vec<GlossaryEntry> {
+        let root = tmp.path();
+        build_glossary(
+            root,
+            &[root.join("AGENTS.md")],
+            &TermIndex::load(&[root.join("ontology")]),
+        )
+    }
+
+    #[test]
+    fn entries_collect_definitions_relations_and_references() {
+        let tmp = project();
+        let entries = entries(&tmp);
+        assert_eq!(entries.len(), 2);
+        let invoice = &entries[0];
+        assert_eq!(invoice.name, "billing/Invoice");
+        assert_eq!(
+            invoice.definition.as_deref(),
+            Some("A request for payment.")
+        );
+        assert_eq!(invoice.aliases, vec!["bill"]);
+        assert_eq!(invoice.is_a, vec!["Entity"]);
+        assert_eq!(invoice.related, vec!["Entity"]);
+        assert_eq!(invoice.path, "ontology/src/billing/invoice.md");
+        assert_eq!(invoice.referenced_by, vec!["AGENTS.md"]);
+    }
+
+    #[test]
+    fn code_examples_are_not_references() {
+        let tmp = project();
+        let root = tmp.path();
+        fs::write(
+            root.join("CLAUDE.md"),
+            "Write `[term:Entity]`, like:\n\n```md\nSend a [term:bill].\n```\n",
+        )
+        .unwrap();
+        let entries = build_glossary(
+            root,
+            &[root.join("AGENTS.md"), root.join("CLAUDE.md")],
+            &TermIndex::load(&[root.join("ontology")]),
+        );
+        assert_eq!(entries[0].referenced_by, vec!["AGENTS.md"]);
+        assert_eq!(entries[1].referenced_by, vec!["AGENTS.md"]);
+    }
+
+    #[test]
+    fn export_formats() {
+        let tmp = project();
+        let entries = entries(&tmp);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export_ontology(&entries, ExportFormat::Json)).unwrap();
+        assert_eq!(json[1]["name"], "Entity");
+
+        let jsonld: serde_json::Value =
+            serde_json::from_str(&export_ontology(&entries, ExportFormat::JsonLd)).unwrap();
+        assert_eq!(jsonld["@graph"][0]["@id"], "urn:ontology:billing/invoice");
+        assert_eq!(
+            jsonld["@graph"][0]["skos:broader"][0]["@id"],
+            "urn:ontology:entity"
+        );
+
+        let skos = export_ontology(&entries, ExportFormat::Skos);
+        assert!(skos.contains("<urn:ontology:entity> a skos:Concept ;\n    skos:prefLabel \"Entity\" ;\n    skos:definition \"Something that \\\"exists\\\".\" .\n"));
+
+        let dot = export_ontology(&entries, ExportFormat::Dot);
+        assert!(dot.contains("\"billing/Invoice\" -> \"Entity\" [label=\"is_a\"];"));
+
+        let md = export_ontology(&entries, ExportFormat::Markdown);
+        assert!(md.starts_with(
+            "# Glossary\n\n## billing/Invoice\n\nA request for payment.\n\n- Aliases: bill\n"
+        ));
+
+        assert_eq!(
+            iri("ops/Context Window"),
+            "urn:ontology:ops/context%20window"
+        );
+        assert_eq!(iri("Caf\u{e9}>1"), "urn:ontology:caf%C3%A9%3E1");
+
+        assert_eq!("jsonld".parse(), Ok(ExportFormat::JsonLd));
+        assert!("yaml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn glossary_injection_and_drift() {
+        let tmp = project();
+        let glossary = render_glossary(&entries(&tmp));
+        assert_eq!(
+            glossary,
+            "- **billing/Invoice** (bill): A request for payment.\n- **Entity**: Something that \"exists\".\n"
+        );
+
+        let content = format!(
+            "# Agents\n\n## Glossary\n\n{}\nold\n{}\n",
+            GLOSSARY_START, GLOSSARY_END
+        );
+        let issues = check_glossary("AGENTS.md", &content, &glossary);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 5);
+        assert_eq!(glossary_lines(&content), Some((5, 7)));
+
+        let updated = inject_glossary(&content, &glossary).unwrap();
+        assert!(updated.ends_with(&format!(
+            "{}\n{}{}\n",
+            GLOSSARY_START, glossary, GLOSSARY_END
+        )));
+        assert!(check_glossary("AGENTS.md", &updated, &glossary).is_empty());
+        assert!(inject_glossary("# No markers\n", &glossary).is_none());
+    }
+
+    #[test]
+    fn fixed_glossary_passes_audit() {
+        let tmp = project();
+        let index = TermIndex::load(&[tmp.path().join("ontology")]);
+        let glossary = render_glossary(&entries(&tmp));
+        let content = format!(
+            "# Agents\n\nEach Invoice is an Entity.\n\n## Glossary\n\n{}\n{}\n",
+            GLOSSARY_START, GLOSSARY_END
+        );
+
+        // `audit --fix`: annotate mentions, then regenerate the glossary.
+        let (fixed, n) = annotate_terms(&content, &index, 5);
+        assert_eq!(n, 2);
+        let fixed = inject_glossary(&fixed, &glossary).unwrap();
+
+        // `audit`: nothing left to report, and a second fix changes nothing.
+        assert!(check_term_mentions("AGENTS.md", &fixed, &index).is_empty());
+        assert!(check_glossary("AGENTS.md", &fixed, &glossary).is_empty());
+        assert_eq!(annotate_terms(&fixed, &index, 5).1, 0);
+    }
+}
@@ -0,0 +1,382 @@
+//! `@path` import validation for instruction files.
+//!
+//! CLAUDE.md (and friends) can pull other files into context with
+//! `@docs/foo.md` references. Imports resolve relative to the importing
+//! file and are followed transitively, so a broken or cyclic import means
+//! context silently goes missing.
+
+use crate::markdown::{mask_code_spans, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+static IMPORT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[\s(\[])@([A-Za-z0-9_.~/][^\s`()\[\]<>,;@]*)").unwrap());
+
+/// Where an `@` import points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// Path relative to the project root (lexically normalized).
+    Local(PathBuf),
+    /// `~/...` or an absolute path — only resolves on one machine.
+    MachineLocal,
+    /// Relative path that climbs above the project root.
+    OutsideRoot,
+}
+
+/// Extract `@path` imports from markdown content.
+///
+/// Returns `(line_number, path)` pairs. Imports inside fenced code blocks
+/// and inline code spans are ignored, as are email addresses and URLs.
+pub fn extract_imports(content: &str) -> Vec<(usize, String)> {
+    let mut imports = Vec::new();
+    for (line_num, line) in prose_lines(content) {
+        let masked = mask_code_spans(line);
+        for cap in IMPORT_RE.captures_iter(&masked) {
+            let path = cap[1].trim_end_matches(['.', ':', '!', '?']);
+            if path.is_empty() || path.contains("://") {
+                continue;
+            }
+            imports.push((line_num, path.to_string()));
+        }
+    }
+    imports
+}
+
+/// Resolve an import relative to the directory of the importing file.
+fn resolve(from_rel: &Path, raw: &str) -> Target {
+    if raw.starts_with('~') || raw.starts_with('/') {
+        return Target::MachineLocal;
+    }
+    let base = from_rel.parent().unwrap_or(Path::new(""));
+    let mut out = PathBuf::new();
+    for comp in base.join(raw).components() {
+        match comp {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return Target::OutsideRoot;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Target::MachineLocal,
+        }
+    }
+    Target::Local(out)
+}
+
+/// Whether an unresolved import looks like a file path rather than an
+/// `@mention` (e.g. a GitHub handle).
+fn looks_like_path(raw: &str) -> bool {
+    raw.contains('/') || raw.contains('.')
+}
+
+/// Existing local files imported by the file at `rel`, in document order.
+pub(crate) fn local_imports(rel: &Path, root: &Path) -> Vec<PathBuf> {
+    let Ok(content) = std::fs::read_to_string(root.join(rel)) else {
+        return Vec::new();
+    };
+    extract_imports(&content)
+        .into_iter()
+        .filter_map(|(_, raw)| match resolve(rel, &raw) {
+            Target::Local(p) if root.join(&p).is_file() => Some(p),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Depth-first search for a path of imports from `from` back to `target`.
+fn find_path_to(
+    from: &Path,
+    target: &Path,
+    root: &Path,
+    visited: &mut HashSet<PathBuf>,
+    chain: &mut Vec<PathBuf>,
+) -> bool {
+    if from == target {
+        return true;
+    }
+    if !visited.insert(from.to_path_buf()) {
+        return false;
+    }
+    for next in local_imports(from, root) {
+        chain.push(next.clone());
+        if find_path_to(&next, target, root, visited, chain) {
+            return true;
+        }
+        chain.pop();
+    }
+    false
+}
+
+/// Check `@path` imports in an instruction file.
+///
+/// Reports imports whose target does not exist, imports that reach outside
+/// the project root or into machine-local paths, and import cycles. A cycle
+/// is reported once, on the file with the lexicographically smallest path
+/// in the cycle.
+pub fn check_imports(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let rel_path = Path::new(rel);
+    let mut issues = Vec::new();
+
+    for (line, raw) in extract_imports(content) {
+        let issue = |message: String, warning: bool| Issue {
+            file: rel.to_string(),
+            line,
+            end_line: 0,
+            message,
+            warning,
+        };
+        match resolve(rel_path, &raw) {
+            Target::MachineLocal => issues.push(issue(
+                format!(
+                    "Machine-local import \"@{}\" \u{2014} import a repo-relative path instead",
+                    raw
+                ),
+                true,
+            )),
+            Target::OutsideRoot => issues.push(issue(
+                format!("Import \"@{}\" reaches outside the project root", raw),
+                true,
+            )),
+            Target::Local(target) => {
+                if !root.join(&target).is_file() {
+                    if looks_like_path(&raw) {
+                        issues.push(issue(
+                            format!(
+                                "Broken import \"@{}\" \u{2014} {} not found",
+                                raw,
+                                target.display()
+                            ),
+                            false,
+                        ));
+                    }
+                    continue;
+                }
+                let mut chain = vec![target.clone()];
+                let mut visited = HashSet::new();
+                if find_path_to(&target, rel_path, root, &mut visited, &mut chain)
+                    && chain.iter().all(|p| p.as_path() >= rel_path)
+                {
+                    let cycle: Vec<String> = std::iter::once(rel.to_string())
+                        .chain(chain.iter().map(|p| p.to_string_lossy().to_string()))
+                        .collect();
+                    issues.push(issue(
+                        format!("Import cycle: {}", cycle.join(" -> ")),
+                        false,
+                    ));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Check `@path` imports in files pulled in by `files` but not among them.
+///
+/// Walks the import closure from each instruction file and runs
+/// [`check_imports`] on every imported file, so a broken import or cycle
+/// two levels down is reported on the file that contains it. Each message
+/// names the chain of imports that loads the file.
+pub fn check_imported_files(files: &[PathBuf], root: &Path) -> Vec<Issue> {
+    let mut start: Vec<PathBuf> = files
+        .iter()
+        .map(|f| f.strip_prefix(root).unwrap_or(f).to_path_buf())
+        .collect();
+    start.sort();
+    let roots: HashSet<PathBuf> = start.iter().cloned().collect();
+    // Imported file -> the chain of files that first reached it.
+    let mut chains: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    let mut queue: VecDeque<(PathBuf, Vec<PathBuf>)> =
+        start.into_iter().map(|f| (f.clone(), vec![f])).collect();
+    while let Some((rel, chain)) = queue.pop_front() {
+        for next in local_imports(&rel, root) {
+            if roots.contains(&next) || chains.contains_key(&next) {
+                continue;
+            }
+            chains.insert(next.clone(), chain.clone());
+            let mut next_chain = chain.clone();
+            next_chain.push(next.clone());
+            queue.push_back((next, next_chain));
+        }
+    }
+
+    let mut issues = Vec::new();
+    for (rel, chain) in chains {
+        let Ok(content) = std::fs::read_to_string(root.join(&rel)) else {
+            continue;
+        };
+        let via: Vec<String> = chain
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        for mut issue in check_imports(&rel.to_string_lossy(), &content, root) {
+            issue.message = format!("{} (imported via {})", issue.message, via.join(" -> "));
+            issues.push(issue);
+        }
+    }
+    issues
+}
+
+/// Collect every file transitively imported by `files`.
+///
+/// Only files that exist under `root` are returned, and files already in
+/// `files` are excluded so callers can count them separately. Paths are
+/// absolute (joined onto `root`) and sorted.
+pub fn import_closure(files: &[PathBuf], root: &Path) -> Vec<PathBuf> {
+    let start: HashSet<PathBuf> = files
+        .iter()
+        .map(|f| f.strip_prefix(root).unwrap_or(f).to_path_buf())
+        .collect();
+    let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut queue: Vec<PathBuf> = start.iter().cloned().collect();
+    while let Some(rel) = queue.pop() {
+        for next in local_imports(&rel, root) {
+            if !start.contains(&next) && seen.insert(next.clone()) {
+                queue.push(next);
+            }
+        }
+    }
+    seen.into_iter().map(|p| root.join(p)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn extract_imports_basic() {
+        let content = "See @docs/foo.md and @README for details.\n";
+        let imports = extract_imports(content);
+        assert_eq!(
+            imports,
+            vec![(1, "docs/foo.md".to_string()), (1, "README".to_string())]
+        );
+    }
+
+    #[test]
+    fn extract_imports_skips_code_emails_and_urls() {
+        let content = "\
+Mail me at dev@example.com, see `@docs/x.md`.
+
+```
+@docs/in-fence.md
+```
+
+Visit https://example.com/@user.
+";
+        assert!(extract_imports(content).is_empty());
+    }
+
+    #[test]
+    fn broken_import_is_error() {
+        let tmp = TempDir::new().unwrap();
+        let issues = check_imports("CLAUDE.md", "Load @docs/missing.md\n", tmp.path());
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("Broken import"));
+        assert!(!issues[0].warning);
+        assert_eq!(issues[0].line, 1);
+    }
+
+    #[test]
+    fn existing_import_resolves_relative_to_file() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src/docs")).unwrap();
+        fs::write(root.join("src/docs/guide.md"), "# Guide\n").unwrap();
+
+        let issues = check_imports("src/CLAUDE.md", "@docs/guide.md\n", root);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn mention_without_path_is_ignored() {
+        let tmp = TempDir::new().unwrap();
+        let issues = check_imports("CLAUDE.md", "Ask @btakita before merging.\n", tmp.path());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn outside_root_and_machine_local_warn() {
+        let tmp = TempDir::new().unwrap();
+        let content = "@../shared/AGENTS.md\n@~/.claude/notes.md\n";
+        let issues = check_imports("CLAUDE.md", content, tmp.path());
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("outside the project root"));
+        assert!(issues[1].message.contains("Machine-local import"));
+        assert!(issues.iter().all(|i| i.warning));
+    }
+
+    #[test]
+    fn import_cycle_reported_once() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("CLAUDE.md"), "@docs/a.md\n").unwrap();
+        fs::write(root.join("docs/a.md"), "@b.md\n").unwrap();
+        fs::write(root.join("docs/b.md"), "@a.md\n").unwrap();
+
+        let a = check_imports("docs/a.md", "@b.md\n", root);
+        let b = check_imports("docs/b.md", "@a.md\n", root);
+        let top = check_imports("CLAUDE.md", "@docs/a.md\n", root);
+        assert_eq!(a.len(), 1);
+        assert_eq!(
+            a[0].message,
+            "Import cycle: docs/a.md -> docs/b.md -> docs/a.md"
+        );
+        assert!(b.is_empty());
+        assert!(top.is_empty());
+    }
+
+    #[test]
+    fn imported_files_are_checked() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("AGENTS.md"), "@docs/a.md\n").unwrap();
+        fs::write(root.join("docs/a.md"), "@b.md\n@missing.md\n").unwrap();
+        fs::write(root.join("docs/b.md"), "@a.md\n").unwrap();
+
+        let issues = check_imported_files(&[root.join("AGENTS.md")], root);
+        let found: Vec<(&str, usize, &str)> = issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "docs/a.md",
+                    1,
+                    "Import cycle: docs/a.md -> docs/b.md -> docs/a.md (imported via AGENTS.md)"
+                ),
+                (
+                    "docs/a.md",
+                    2,
+                    "Broken import \"@missing.md\" \u{2014} docs/missing.md not found (imported via AGENTS.md)"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_closure_is_transitive() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("CLAUDE.md"), "@docs/a.md\n").unwrap();
+        fs::write(root.join("docs/a.md"), "@b.md\n@../CLAUDE.md\n").unwrap();
+        fs::write(root.join("docs/b.md"), "leaf\n").unwrap();
+
+        let closure = import_closure(&[root.join("CLAUDE.md")], root);
+        assert_eq!(
+            closure,
+            vec![root.join("docs/a.md"), root.join("docs/b.md")]
+        );
+    }
+}
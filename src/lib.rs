@@ -1,34 +1,95 @@
 //! Discovery, auditing, and sync for AGENTS.md/CLAUDE.md instruction files.
 
 mod audit;
+mod commands;
+mod config;
+mod context;
 mod discovery;
+#[cfg(feature = "ontology")]
+pub mod glossary;
+mod hidden;
+mod imports;
+mod links;
+mod manifest;
+mod markdown;
 pub use agent_runbooks as runbooks;
 #[cfg(feature = "ontology")]
 pub mod ontology;
+mod runbook_audit;
+mod runbook_extract;
+mod runbook_upgrade;
+mod secrets;
+mod security;
+mod shell;
+mod skill;
+mod snippets;
 #[cfg(feature = "spec-audit")]
 pub mod spec_audit;
+mod symbols;
+mod syntax;
 mod types;
+#[cfg(feature = "check-urls")]
+pub mod urls;
+mod verify;
 
 pub use audit::{
     check_actionable, check_context_invariant, check_line_budget, check_staleness,
     check_tree_paths,
 };
+pub use commands::{check_commands, extract_commands};
+pub use config::{
+    CONFIG_FILE, OntologyConfig, ProjectConfig, RunbookConfig, SecretRule, SecretsConfig,
+    SecurityConfig, SecurityRule, SymbolConfig, UrlConfig, VerifyConfig,
+};
+pub use context::{
+    ContextEntry, ContextSource, effective_context, estimate_tokens, relative_to_root,
+};
 pub use discovery::{find_instruction_files, find_root};
 #[cfg(feature = "ontology")]
-pub use ontology::check_ontology_terms;
+pub use glossary::{
+    ExportFormat, GLOSSARY_END, GLOSSARY_START, GlossaryEntry, build_glossary, check_glossary,
+    export_ontology, inject_glossary, render_glossary,
+};
+pub use hidden::check_hidden_content;
+pub use imports::{check_imported_files, check_imports, extract_imports, import_closure};
+pub use links::{Link, check_links, extract_links};
+pub use manifest::{Manifest, check_manifest_refs, load_manifests};
+#[cfg(feature = "ontology")]
+pub use ontology::{
+    EXISTENCE_MANIFEST, Term, TermCase, TermIndex, TermMention, annotate_terms,
+    check_ontology_coverage, check_ontology_terms, check_term_files, check_term_mentions,
+    check_unconfigured_terms, find_term_mentions, resolve_ontology_dirs,
+};
+pub use runbook_audit::{RUNBOOK_DIR, check_runbook, check_runbook_refs, is_runbook};
+pub use runbook_extract::{Extraction, Section, extract_runbook, runbook_candidates, runbook_slug};
+pub use runbook_upgrade::{
+    BUNDLED_LOCK, BundledRecord, UpgradeStatus, check_runbook_upgrades, content_hash,
+    scaffold_runbooks, upgrade_runbooks,
+};
 pub use runbooks::init_runbooks;
+pub use secrets::{RULES as SECRET_RULES, check_secrets, redact_secrets};
+pub use security::{RULES as SECURITY_RULES, check_dangerous};
+pub use shell::check_shell;
+pub use skill::{MAX_DESCRIPTION_LEN, MAX_NAME_LEN, check_skill, is_skill_file};
+pub use snippets::{SnippetSource, check_snippets, fix_snippets};
+pub use symbols::{SymbolIndex, check_symbols, extract_symbol_refs};
+pub use syntax::check_code_syntax;
 pub use types::{AuditConfig, Issue, is_agent_file};
+#[cfg(feature = "check-urls")]
+pub use urls::check_urls;
+pub use verify::{VERIFY_ATTR, VerifyBlock, extract_verify_blocks, verify_snippets};
 
 use agent_kit::audit_common::LINE_BUDGET;
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 
 /// Bundled SKILL.md content for the instruction-files skill.
-const BUNDLED_SKILL: &str = include_str!("../.claude/skills/instruction-files/SKILL.md");
+pub(crate) const BUNDLED_SKILL: &str = include_str!("../.claude/skills/instruction-files/SKILL.md");
 
 /// Initialize instruction-files in a project.
 ///
-/// Installs the bundled SKILL.md and scaffolds default runbooks.
+/// Installs the bundled SKILL.md (after validating it with [`check_skill`])
+/// and scaffolds default runbooks.
 /// Safe to call repeatedly — never overwrites existing files.
 ///
 /// Returns the paths of files written.
@@ -38,6 +99,18 @@ pub fn init(root: &Path) -> Result<Vec<PathBuf>> {
     // Install SKILL.md — always target Claude Code paths since skills are Claude Code only
     let skill_path = agent_kit::detect::Environment::ClaudeCode.skill_path("instruction-files", Some(root));
     if !skill_path.exists() {
+        let rel = skill_path.strip_prefix(root).unwrap_or(&skill_path);
+        let errors: Vec<String> = check_skill(&rel.to_string_lossy(), BUNDLED_SKILL, root)
+            .into_iter()
+            .filter(|i| !i.warning)
+            .map(|i| i.message)
+            .collect();
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "bundled SKILL.md is invalid, not installing it: {}",
+                errors.join("; ")
+            );
+        }
         if let Some(parent) = skill_path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("failed to create {}", parent.display()))?;
@@ -47,8 +120,8 @@ pub fn init(root: &Path) -> Result<Vec<PathBuf>> {
         written.push(skill_path);
     }
 
-    // Scaffold default runbooks
-    let n = init_runbooks(root)?;
+    // Scaffold default runbooks, recording what was bundled for upgrades
+    let n = scaffold_runbooks(root)?;
     if n > 0 {
         let runbooks_dir = root.join(".agent/runbooks");
         written.push(runbooks_dir);
@@ -57,26 +130,97 @@ pub fn init(root: &Path) -> Result<Vec<PathBuf>> {
     Ok(written)
 }
 
-/// Run the full audit with the given configuration.
+/// Options for [`run_with_options`] that toggle optional checks.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Check external http(s) links (requires the `check-urls` feature).
+    pub check_urls: bool,
+    /// Rewrite fixable issues in place (drifted quoted snippets, and
+    /// unannotated ontology term mentions up to
+    /// `[ontology] max_annotations_per_file`).
+    pub fix: bool,
+    /// Warn when `[term:Name]` annotations differ in case from the term's
+    /// canonical spelling (requires the `ontology` feature).
+    pub strict_term_case: bool,
+}
+
+/// Run the full audit with the given configuration and default
+/// [`RunOptions`].
 ///
 /// Returns `Ok(())` on success, calls `std::process::exit(1)` on issues found.
-///
-/// When the `ontology` feature is enabled and `ontology_dir` is provided,
-/// instruction files are also scanned for `[term:Name]` annotations and
-/// each term is verified against the ontology directory.
+/// See [`run_with_options`] for the optional checks and ontology handling;
+/// `ontology_dir`, when given, replaces the configured ontology directories.
 pub fn run(
     config: &AuditConfig,
     root_override: Option<&Path>,
     #[cfg(feature = "ontology")] ontology_dir: Option<&Path>,
 ) -> Result<()> {
+    #[cfg(feature = "ontology")]
+    let ontology_dirs: Vec<PathBuf> = ontology_dir.map(Path::to_path_buf).into_iter().collect();
+    run_with_options(
+        config,
+        root_override,
+        &RunOptions::default(),
+        #[cfg(feature = "ontology")]
+        &ontology_dirs,
+    )
+}
+
+/// Run the full audit with the given configuration and options.
+///
+/// Returns `Ok(())` on success, calls `std::process::exit(1)` on issues found.
+/// Per-project settings are read from `instruction-files.toml` at the root.
+///
+/// When the `ontology` feature is enabled, `ontology_dirs` (or, if empty,
+/// the directories from `[ontology] dirs` or an `existence.toml` manifest;
+/// see [`resolve_ontology_dirs`]) are loaded and every `[term:Name]`
+/// annotation is verified against them (earlier ones take precedence);
+/// unused and colliding terms and malformed term files are reported, a
+/// generated glossary section is checked for drift, and a per-term usage
+/// count is printed. Annotations with no ontology configured are warnings.
+pub fn run_with_options(
+    config: &AuditConfig,
+    root_override: Option<&Path>,
+    options: &RunOptions,
+    #[cfg(feature = "ontology")] ontology_dirs: &[PathBuf],
+) -> Result<()> {
+    if options.check_urls && cfg!(not(feature = "check-urls")) {
+        anyhow::bail!("--check-urls requires the `check-urls` feature");
+    }
+
     println!("Auditing docs...\n");
 
     let root = match root_override {
         Some(p) => p.to_path_buf(),
         None => find_root(config),
     };
+    let project = ProjectConfig::load(&root)?;
     let files = find_instruction_files(&root, config);
+    let symbols = SymbolIndex::load(&root, config);
     let mut issues: Vec<Issue> = Vec::new();
+    #[cfg(feature = "ontology")]
+    let ontology_dirs = &resolve_ontology_dirs(&root, ontology_dirs, &project.ontology.dirs);
+    #[cfg(feature = "ontology")]
+    for dir in ontology_dirs {
+        if !dir.join("src").is_dir() {
+            issues.push(Issue {
+                file: dir.strip_prefix(&root).unwrap_or(dir).display().to_string(),
+                line: 0,
+                end_line: 0,
+                message: "Ontology directory has no src/ directory; no terms were loaded"
+                    .to_string(),
+                warning: true,
+            });
+        }
+    }
+    #[cfg(feature = "ontology")]
+    let terms = TermIndex::load(ontology_dirs);
+    #[cfg(feature = "ontology")]
+    let glossary = if ontology_dirs.is_empty() {
+        String::new()
+    } else {
+        render_glossary(&build_glossary(&root, &files, &terms))
+    };
 
     for doc in &files {
         let rel = doc
@@ -84,34 +228,94 @@ pub fn run(
             .unwrap_or(doc)
             .to_string_lossy()
             .to_string();
-        if let Ok(content) = std::fs::read_to_string(doc) {
+        if let Ok(mut content) = std::fs::read_to_string(doc) {
+            if options.fix {
+                let (fixed, n) = fix_snippets(&content, &root);
+                if n > 0 {
+                    std::fs::write(doc, &fixed)
+                        .with_context(|| format!("failed to write {}", doc.display()))?;
+                    println!("  Refreshed {} snippet(s) in {}", n, rel);
+                    content = fixed;
+                }
+                #[cfg(feature = "ontology")]
+                if !ontology_dirs.is_empty() {
+                    let limit = project.ontology.max_annotations_per_file;
+                    let (fixed, n) = annotate_terms(&content, &terms, limit);
+                    if n > 0 {
+                        std::fs::write(doc, &fixed)
+                            .with_context(|| format!("failed to write {}", doc.display()))?;
+                        println!("  Annotated {} term mention(s) in {}", n, rel);
+                        content = fixed;
+                    }
+                    if let Some(fixed) = inject_glossary(&content, &glossary)
+                        && fixed != content
+                    {
+                        std::fs::write(doc, &fixed)
+                            .with_context(|| format!("failed to write {}", doc.display()))?;
+                        println!("  Regenerated glossary in {}", rel);
+                        content = fixed;
+                    }
+                }
+            }
+            issues.extend(check_hidden_content(&rel, &content));
             issues.extend(check_tree_paths(&rel, &content, &root));
             issues.extend(check_actionable(&rel, &content, config));
             issues.extend(check_context_invariant(&rel, &content, config));
+            issues.extend(check_imports(&rel, &content, &root));
+            issues.extend(check_links(&rel, &content, &root));
+            issues.extend(check_commands(&rel, &content, &root));
+            issues.extend(check_manifest_refs(&rel, &content, &root));
+            issues.extend(check_symbols(&rel, &content, &symbols, &project.symbols));
+            issues.extend(check_snippets(&rel, &content, &root));
+            issues.extend(check_code_syntax(&rel, &content));
+            issues.extend(check_shell(&rel, &content, &root));
+            issues.extend(extract_verify_blocks(&rel, &content).1);
+            issues.extend(check_dangerous(&rel, &content, &project.security));
+            issues.extend(check_secrets(&rel, &content, &project.secrets));
+            if is_runbook(&rel) {
+                issues.extend(check_runbook(&rel, &content, &project.runbooks));
+            }
+            if is_skill_file(&rel) {
+                issues.extend(check_skill(&rel, &content, &root));
+            }
             #[cfg(feature = "ontology")]
-            if let Some(onto_dir) = ontology_dir {
-                issues.extend(check_ontology_terms(&rel, &content, onto_dir));
+            if ontology_dirs.is_empty() {
+                issues.extend(check_unconfigured_terms(&rel, &content));
+            } else {
+                let case = if options.strict_term_case {
+                    TermCase::Strict
+                } else {
+                    TermCase::Insensitive
+                };
+                issues.extend(check_ontology_terms(&rel, &content, &terms, case));
+                issues.extend(check_term_mentions(&rel, &content, &terms));
+                issues.extend(check_glossary(&rel, &content, &glossary));
             }
         }
     }
 
     let (budget_issues, counts, total) = check_line_budget(&files, &root, config);
     issues.extend(budget_issues);
+    issues.extend(check_imported_files(&files, &root));
     issues.extend(check_staleness(&files, &root, config));
-
-    for issue in &issues {
-        let mut loc = format!("  {}", issue.file);
-        if issue.line > 0 {
-            if issue.end_line > issue.line {
-                loc.push_str(&format!(":{}-{}", issue.line, issue.end_line));
-            } else {
-                loc.push_str(&format!(":{}", issue.line));
-            }
-        }
-        let marker = if issue.warning { "\u{26a0}" } else { "\u{2717}" };
-        println!("{:<50} {} {}", loc, marker, issue.message);
+    issues.extend(check_runbook_refs(&files, &root));
+    issues.extend(check_runbook_upgrades(&root));
+    #[cfg(feature = "ontology")]
+    let term_usage = if ontology_dirs.is_empty() {
+        Vec::new()
+    } else {
+        let (coverage_issues, usage) = check_ontology_coverage(&files, &root, &terms);
+        issues.extend(coverage_issues);
+        issues.extend(check_term_files(&root, &terms));
+        usage
+    };
+    #[cfg(feature = "check-urls")]
+    if options.check_urls {
+        issues.extend(check_urls(&files, &root, &project.urls));
     }
 
+    print_issues(&issues, &project.secrets);
+
     let mark = if total <= LINE_BUDGET {
         "\u{2713}"
     } else {
@@ -124,6 +328,14 @@ pub fn run(
     for (name, n) in &counts {
         println!("  {}: {}", name, n);
     }
+    #[cfg(feature = "ontology")]
+    if !term_usage.is_empty() {
+        let used = term_usage.iter().filter(|(_, n)| *n > 0).count();
+        println!("\nOntology terms referenced: {}/{}", used, term_usage.len());
+        for (term, n) in &term_usage {
+            println!("  {}: {}", term, n);
+        }
+    }
 
     let n = issues.len();
     if n > 0 {
@@ -135,3 +347,319 @@ pub fn run(
 
     Ok(())
 }
+
+/// Print issues as `file:line  marker message` rows.
+/// Print `issues`, redacting built-in and `config.rules` secret matches.
+fn print_issues(issues: &[Issue], config: &SecretsConfig) {
+    let custom = secrets::custom_rules(config);
+    for issue in issues {
+        let mut loc = format!("  {}", issue.file);
+        if issue.line > 0 {
+            if issue.end_line > issue.line {
+                loc.push_str(&format!(":{}-{}", issue.line, issue.end_line));
+            } else {
+                loc.push_str(&format!(":{}", issue.line));
+            }
+        }
+        let marker = if issue.warning {
+            "\u{26a0}"
+        } else {
+            "\u{2717}"
+        };
+        println!(
+            "{:<50} {} {}",
+            loc,
+            marker,
+            secrets::redact_with_rules(&issue.message, &custom)
+        );
+    }
+}
+
+/// Run every `verify`-tagged shell block in the instruction files.
+///
+/// Each file's blocks run in a sandbox copy of the project (see
+/// [`verify_snippets`]). Returns `Ok(())` when all pass, calls
+/// `std::process::exit(1)` on failures.
+pub fn run_verify(config: &AuditConfig, root_override: Option<&Path>) -> Result<()> {
+    let root = match root_override {
+        Some(p) => p.to_path_buf(),
+        None => find_root(config),
+    };
+    let project = ProjectConfig::load(&root)?;
+    let files = find_instruction_files(&root, config);
+    let mut issues: Vec<Issue> = Vec::new();
+    let mut total = 0;
+
+    for doc in &files {
+        let rel = doc
+            .strip_prefix(&root)
+            .unwrap_or(doc)
+            .to_string_lossy()
+            .to_string();
+        if let Ok(content) = std::fs::read_to_string(doc) {
+            let (blocks, _) = extract_verify_blocks(&rel, &content);
+            if !blocks.is_empty() {
+                println!("Verifying {} snippet(s) in {}...", blocks.len(), rel);
+            }
+            total += blocks.len();
+            issues.extend(verify_snippets(
+                &rel,
+                &content,
+                &root,
+                &project.verify,
+                &config.skip_dirs,
+            )?);
+        }
+    }
+
+    print_issues(&issues, &project.secrets);
+    let n = issues.len();
+    if n > 0 {
+        println!("\nFound {} issue(s)", n);
+        std::process::exit(1);
+    } else {
+        println!("\n{} snippet(s) verified \u{2713}", total);
+    }
+
+    Ok(())
+}
+
+/// Project root, config, instruction files and loaded terms for the
+/// ontology commands. Fails when no ontology is configured.
+#[cfg(feature = "ontology")]
+fn load_ontology(
+    config: &AuditConfig,
+    root_override: Option<&Path>,
+    ontology_dirs: &[PathBuf],
+) -> Result<(PathBuf, ProjectConfig, Vec<PathBuf>, TermIndex)> {
+    let root = match root_override {
+        Some(p) => p.to_path_buf(),
+        None => find_root(config),
+    };
+    let project = ProjectConfig::load(&root)?;
+    let ontology_dirs = &resolve_ontology_dirs(&root, ontology_dirs, &project.ontology.dirs);
+    if ontology_dirs.is_empty() {
+        anyhow::bail!(
+            "no ontology configured (pass --ontology-dir, set `[ontology] dirs` in {}, or add an {})",
+            CONFIG_FILE,
+            EXISTENCE_MANIFEST
+        );
+    }
+    let files = find_instruction_files(&root, config);
+    Ok((root, project, files, TermIndex::load(ontology_dirs)))
+}
+
+/// Print the ontology in `format` to stdout.
+///
+/// Each term carries its definition, aliases, relations and the instruction
+/// files that annotate it (see [`build_glossary`]).
+#[cfg(feature = "ontology")]
+pub fn run_ontology_export(
+    config: &AuditConfig,
+    root_override: Option<&Path>,
+    ontology_dirs: &[PathBuf],
+    format: ExportFormat,
+) -> Result<()> {
+    let (root, _, files, terms) = load_ontology(config, root_override, ontology_dirs)?;
+    let entries = build_glossary(&root, &files, &terms);
+    print!("{}", export_ontology(&entries, format));
+    Ok(())
+}
+
+/// Regenerate the glossary between [`GLOSSARY_START`] and [`GLOSSARY_END`]
+/// in every instruction file that has the markers.
+///
+/// With `check`, nothing is written: files whose glossary is out of date
+/// are reported and the process exits with status 1.
+#[cfg(feature = "ontology")]
+pub fn run_glossary(
+    config: &AuditConfig,
+    root_override: Option<&Path>,
+    ontology_dirs: &[PathBuf],
+    check: bool,
+) -> Result<()> {
+    let (root, project, files, terms) = load_ontology(config, root_override, ontology_dirs)?;
+    let glossary = render_glossary(&build_glossary(&root, &files, &terms));
+    let mut issues: Vec<Issue> = Vec::new();
+    let mut found = 0;
+
+    for doc in &files {
+        let rel = doc
+            .strip_prefix(&root)
+            .unwrap_or(doc)
+            .to_string_lossy()
+            .to_string();
+        let Ok(content) = std::fs::read_to_string(doc) else {
+            continue;
+        };
+        let Some(updated) = inject_glossary(&content, &glossary) else {
+            continue;
+        };
+        found += 1;
+        if check {
+            issues.extend(check_glossary(&rel, &content, &glossary));
+        } else if updated != content {
+            std::fs::write(doc, &updated)
+                .with_context(|| format!("failed to write {}", doc.display()))?;
+            println!("  Regenerated glossary in {}", rel);
+        }
+    }
+
+    if found == 0 {
+        println!(
+            "No glossary markers found. Add `{}` and `{}` to an instruction file.",
+            GLOSSARY_START, GLOSSARY_END
+        );
+    }
+    print_issues(&issues, &project.secrets);
+    if !issues.is_empty() {
+        println!("\nFound {} issue(s)", issues.len());
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Merge newer bundled runbooks into the project's scaffolded copies.
+///
+/// See [`upgrade_runbooks`]. With `check`, nothing is written: outdated
+/// runbooks are listed and the process exits with status 1.
+pub fn run_runbooks_upgrade(root: &Path, check: bool) -> Result<()> {
+    let results = upgrade_runbooks(root, !check)?;
+    for (rel, status) in &results {
+        let note = match status {
+            UpgradeStatus::Current => continue,
+            UpgradeStatus::Outdated => "Outdated".to_string(),
+            UpgradeStatus::Updated => "Updated".to_string(),
+            UpgradeStatus::Merged => "Merged".to_string(),
+            UpgradeStatus::Conflicted(n) => format!("{} conflict(s), resolve the markers", n),
+            UpgradeStatus::Untracked => {
+                "Skipped (customized, no scaffold record to merge against)".to_string()
+            }
+        };
+        println!("  {}: {}", note, rel);
+    }
+
+    let pending = results
+        .iter()
+        .filter(|(_, s)| matches!(s, UpgradeStatus::Outdated | UpgradeStatus::Conflicted(_)))
+        .count();
+    if pending > 0 {
+        if check {
+            println!(
+                "\n{} runbook(s) outdated \u{2014} run `instruction-files runbooks upgrade`",
+                pending
+            );
+        } else {
+            println!("\n{} runbook(s) need conflict resolution", pending);
+        }
+        std::process::exit(1);
+    } else if results.iter().all(|(_, s)| *s == UpgradeStatus::Current) {
+        println!("Runbooks are up to date.");
+    }
+
+    Ok(())
+}
+
+/// Move a section of an instruction file into a runbook, or list candidates.
+///
+/// With `section`, the section is written to `.agent/runbooks/<slug>.md`
+/// and replaced with a pointer (see [`extract_runbook`]). Without it,
+/// numbered-step sections of at least `min_lines` lines (default
+/// `[runbooks] candidate_min_lines`) are listed and nothing is written.
+/// Either way the combined line budget is recomputed. `file` must be
+/// under the project root; anything else is rejected before any write.
+pub fn run_extract_runbook(
+    config: &AuditConfig,
+    root_override: Option<&Path>,
+    file: &Path,
+    section: Option<&str>,
+    min_lines: Option<usize>,
+) -> Result<()> {
+    let root = match root_override {
+        Some(p) => p.to_path_buf(),
+        None => find_root(config),
+    };
+    let rel = relative_to_root(&root, file)?;
+    let project = ProjectConfig::load(&root)?;
+    let path = root.join(&rel);
+    let rel = rel.to_string_lossy().to_string();
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let (_, counts, before) =
+        check_line_budget(&find_instruction_files(&root, config), &root, config);
+    // What the file adds to the budget; zero when it isn't loaded.
+    let contribution = counts
+        .iter()
+        .find(|(name, _)| *name == rel || name.strip_suffix(" (imported)") == Some(rel.as_str()))
+        .map_or(0, |(_, n)| *n);
+
+    let after = match section {
+        Some(title) => {
+            let extraction = extract_runbook(&rel, &content, title)?;
+            let runbook_path = root
+                .join(RUNBOOK_DIR)
+                .join(format!("{}.md", extraction.slug));
+            if runbook_path.exists() {
+                anyhow::bail!("{} already exists", runbook_path.display());
+            }
+            std::fs::create_dir_all(root.join(RUNBOOK_DIR))
+                .with_context(|| format!("failed to create {}", RUNBOOK_DIR))?;
+            std::fs::write(&runbook_path, &extraction.runbook)
+                .with_context(|| format!("failed to write {}", runbook_path.display()))?;
+            std::fs::write(&path, &extraction.content)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "  Moved \"{}\" ({} lines) from {} to {}/{}.md",
+                extraction.section.title,
+                extraction.section.lines(),
+                rel,
+                RUNBOOK_DIR,
+                extraction.slug
+            );
+            check_line_budget(&find_instruction_files(&root, config), &root, config).2
+        }
+        None => {
+            let min = min_lines.unwrap_or(project.runbooks.candidate_min_lines);
+            let candidates = runbook_candidates(&content, min);
+            if candidates.is_empty() {
+                println!("No numbered-step sections of {}+ lines in {}.", min, rel);
+                return Ok(());
+            }
+            for c in &candidates {
+                println!(
+                    "  {}:{}-{}  {} ({} lines, {} steps)",
+                    rel,
+                    c.start,
+                    c.end,
+                    c.title,
+                    c.lines(),
+                    c.steps
+                );
+            }
+            println!(
+                "\nExtract one with: instruction-files extract-runbook {} --section \"<title>\"",
+                rel
+            );
+            let saved: usize = candidates.iter().map(|c| c.lines() - 1).sum();
+            before.saturating_sub(saved.min(contribution))
+        }
+    };
+
+    let mark = if after <= LINE_BUDGET {
+        "\u{2713}"
+    } else {
+        "\u{2717}"
+    };
+    let label = if section.is_some() {
+        "now"
+    } else {
+        "after extracting all"
+    };
+    println!(
+        "\nCombined instruction files: {} lines, {} {} (budget: {}) {}",
+        before, label, after, LINE_BUDGET, mark
+    );
+
+    Ok(())
+}
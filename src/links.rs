@@ -0,0 +1,271 @@
+//! Relative link and anchor validation for instruction files.
+//!
+//! Resolves markdown links, images and reference definitions against the
+//! linking file's directory and checks that `#anchor` fragments match a
+//! heading in the target file. Absolute URLs are left to the external URL
+//! checker.
+
+use crate::markdown::{anchors, mask_code_spans, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Component, Path, PathBuf};
+
+static INLINE_LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(!?)\[[^\]]*\]\(\s*(<[^>]*>|[^\s)]+)(?:\s+(?:"[^"]*"|'[^']*'))?\s*\)"#).unwrap()
+});
+static REF_DEF_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s{0,3}\[[^\]]+\]:\s*(<[^>]*>|\S+)").unwrap());
+static SCHEME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap());
+
+/// A link found in markdown content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// 1-based line number.
+    pub line: usize,
+    /// Raw link destination (angle brackets removed).
+    pub target: String,
+    /// `![alt](src)` image reference.
+    pub image: bool,
+}
+
+impl Link {
+    /// Whether the destination has a URL scheme (`https:`, `mailto:`, ...).
+    pub fn is_absolute_url(&self) -> bool {
+        SCHEME_RE.is_match(&self.target) || self.target.starts_with("//")
+    }
+}
+
+/// Extract inline links, images and reference definitions from markdown.
+///
+/// Links inside fenced code blocks and inline code spans are ignored.
+pub fn extract_links(content: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    for (line, text) in prose_lines(content) {
+        let masked = mask_code_spans(text);
+        let clean = |t: &str| t.trim_start_matches('<').trim_end_matches('>').to_string();
+        for cap in INLINE_LINK_RE.captures_iter(&masked) {
+            links.push(Link {
+                line,
+                target: clean(&cap[2]),
+                image: !cap[1].is_empty(),
+            });
+        }
+        if let Some(cap) = REF_DEF_RE.captures(&masked) {
+            links.push(Link {
+                line,
+                target: clean(&cap[1]),
+                image: false,
+            });
+        }
+    }
+    links
+}
+
+/// Lexically resolve `target` against the directory of `from_rel`.
+///
+/// A leading `/` is treated as root-relative. Returns `None` when the path
+/// climbs above the project root.
+pub(crate) fn resolve(from_rel: &Path, target: &str) -> Option<PathBuf> {
+    let joined = match target.strip_prefix('/') {
+        Some(rooted) => PathBuf::from(rooted),
+        None => from_rel.parent().unwrap_or(Path::new("")).join(target),
+    };
+    let mut out = PathBuf::new();
+    for comp in joined.components() {
+        match comp {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    Some(out)
+}
+
+/// Check relative links, image paths and `#anchor` fragments.
+///
+/// Absolute URLs are skipped. Anchors are only verified for markdown
+/// targets; a fragment on any other file type is ignored.
+pub fn check_links(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let rel_path = Path::new(rel);
+    let mut issues = Vec::new();
+
+    for link in extract_links(content) {
+        if link.is_absolute_url() || link.target.is_empty() {
+            continue;
+        }
+        let (path_part, fragment) = match link.target.split_once('#') {
+            Some((p, f)) => (p, Some(f)),
+            None => (link.target.as_str(), None),
+        };
+        let path_part = path_part
+            .split('?')
+            .next()
+            .unwrap_or("")
+            .replace("%20", " ");
+        let kind = if link.image { "image" } else { "link" };
+        let issue = |message: String| Issue {
+            file: rel.to_string(),
+            line: link.line,
+            end_line: 0,
+            message,
+            warning: false,
+        };
+        let warn = |message: String| Issue {
+            warning: true,
+            ..issue(message)
+        };
+
+        let (target_rel, target_content) = if path_part.is_empty() {
+            (rel_path.to_path_buf(), Some(content.to_string()))
+        } else {
+            let Some(resolved) = resolve(rel_path, &path_part) else {
+                issues.push(warn(format!(
+                    "Unchecked {} \"{}\" \u{2014} reaches outside the project root",
+                    kind, link.target
+                )));
+                continue;
+            };
+            let abs = root.join(&resolved);
+            if !abs.exists() {
+                issues.push(issue(format!(
+                    "Broken {} \"{}\" \u{2014} {} not found",
+                    kind,
+                    link.target,
+                    resolved.display()
+                )));
+                continue;
+            }
+            let is_md = resolved.extension().and_then(|e| e.to_str()) == Some("md");
+            let text = if is_md && abs.is_file() {
+                std::fs::read_to_string(&abs).ok()
+            } else {
+                None
+            };
+            (resolved, text)
+        };
+
+        if let (Some(fragment), Some(text)) = (fragment, target_content)
+            && !fragment.is_empty()
+            && !anchors(&text)
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(fragment))
+        {
+            issues.push(issue(format!(
+                "Broken anchor \"#{}\" \u{2014} no matching heading in {}",
+                fragment,
+                target_rel.display()
+            )));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".agent/runbooks")).unwrap();
+        fs::create_dir_all(root.join("docs/img")).unwrap();
+        fs::write(
+            root.join(".agent/runbooks/precommit.md"),
+            "# Precommit\n\n## Steps\n\n1. Test\n",
+        )
+        .unwrap();
+        fs::write(root.join("docs/img/arch.png"), "png").unwrap();
+        tmp
+    }
+
+    #[test]
+    fn extract_links_inline_image_and_reference() {
+        let content = "\
+See [runbook](.agent/runbooks/precommit.md#steps \"Steps\") and ![arch](docs/img/arch.png).
+
+[ref]: <docs/guide.md>
+
+`[not](a-link.md)`
+
+```
+[also not](fenced.md)
+```
+";
+        let links = extract_links(content);
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].target, ".agent/runbooks/precommit.md#steps");
+        assert!(!links[0].image);
+        assert_eq!(links[1].target, "docs/img/arch.png");
+        assert!(links[1].image);
+        assert_eq!(
+            links[2],
+            Link {
+                line: 3,
+                target: "docs/guide.md".to_string(),
+                image: false
+            }
+        );
+    }
+
+    #[test]
+    fn valid_links_and_anchors_pass() {
+        let tmp = setup();
+        let content = "\
+# Doc
+
+## Local Section
+
+- [runbook](.agent/runbooks/precommit.md#steps)
+- ![arch](docs/img/arch.png)
+- [here](#local-section)
+- [site](https://example.com/missing#nope)
+";
+        let issues = check_links("AGENTS.md", content, tmp.path());
+        assert!(
+            issues.is_empty(),
+            "unexpected: {:?}",
+            issues.iter().map(|i| &i.message).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn broken_link_and_image_reported_with_line() {
+        let tmp = setup();
+        let content = "# Doc\n\n[gone](docs/gone.md)\n![img](docs/img/missing.png)\n";
+        let issues = check_links("AGENTS.md", content, tmp.path());
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].line, 3);
+        assert!(issues[0].message.contains("Broken link"));
+        assert_eq!(issues[1].line, 4);
+        assert!(issues[1].message.contains("Broken image"));
+        assert!(!issues[0].warning);
+    }
+
+    #[test]
+    fn broken_anchor_reported() {
+        let tmp = setup();
+        let content = "[steps](.agent/runbooks/precommit.md#preconditions)\n[self](#nowhere)\n";
+        let issues = check_links("AGENTS.md", content, tmp.path());
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("#preconditions"));
+        assert!(issues[0].message.contains(".agent/runbooks/precommit.md"));
+        assert!(issues[1].message.contains("#nowhere"));
+    }
+
+    #[test]
+    fn links_resolve_relative_to_file_dir() {
+        let tmp = setup();
+        let content = "[precommit](precommit.md#steps) and [up](../../docs/img/arch.png)\n";
+        let issues = check_links(".agent/runbooks/prerelease.md", content, tmp.path());
+        assert!(issues.is_empty());
+    }
+}
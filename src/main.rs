@@ -1,6 +1,6 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use instruction_files::AuditConfig;
+use instruction_files::{AuditConfig, ContextSource, RunOptions};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -22,9 +22,37 @@ enum Commands {
         #[arg(long, default_value_t = true)]
         broad: bool,
 
-        /// Ontology directory for validating [term:Name] annotations (requires ontology feature)
+        /// Ontology directory for validating [term:Name] annotations (requires ontology feature).
+        /// Repeat for multiple sources; earlier ones take precedence.
+        /// Default: `[ontology] dirs` in instruction-files.toml, or an existence.toml manifest.
         #[arg(long)]
-        ontology_dir: Option<PathBuf>,
+        ontology_dir: Vec<PathBuf>,
+
+        /// Check external http(s) links (requires check-urls feature)
+        #[arg(long)]
+        check_urls: bool,
+
+        /// Rewrite fixable issues in place (refresh drifted quoted snippets, annotate term mentions)
+        #[arg(long)]
+        fix: bool,
+
+        /// Warn when [term:Name] annotations differ in case from the canonical spelling
+        #[arg(long)]
+        strict_term_case: bool,
+    },
+
+    /// Show the instruction files an agent loads when working on a path
+    Context {
+        /// File the agent is working on (relative to CWD or absolute)
+        path: PathBuf,
+
+        /// Project root (default: auto-detect from CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Print the merged content instead of the file list
+        #[arg(long)]
+        concat: bool,
     },
 
     /// Initialize .agent/runbooks/ with bundled defaults
@@ -40,6 +68,92 @@ enum Commands {
         #[arg(short, long)]
         root: Option<PathBuf>,
     },
+
+    /// Run `verify`-tagged shell blocks in a sandbox copy of the project
+    VerifySnippets {
+        /// Project root (default: auto-detect from CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+    },
+
+    /// Move a procedure section into .agent/runbooks/, or list candidates
+    ExtractRunbook {
+        /// Instruction file to extract from
+        file: PathBuf,
+
+        /// Heading of the section to move (omit to list candidates)
+        #[arg(long)]
+        section: Option<String>,
+
+        /// Project root (default: auto-detect from CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Minimum candidate length in lines (default: [runbooks] candidate_min_lines)
+        #[arg(long)]
+        min_lines: Option<usize>,
+    },
+
+    /// Manage scaffolded runbooks
+    Runbooks {
+        #[command(subcommand)]
+        command: RunbookCommands,
+    },
+
+    /// Export the ontology or regenerate glossaries (requires ontology feature)
+    #[cfg(feature = "ontology")]
+    Ontology {
+        #[command(subcommand)]
+        command: OntologyCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum RunbookCommands {
+    /// Merge newer bundled runbooks into customized local copies
+    Upgrade {
+        /// Project root (default: CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Report outdated runbooks instead of upgrading them
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[cfg(feature = "ontology")]
+#[derive(Subcommand)]
+enum OntologyCommands {
+    /// Print every term with its definition, aliases, relations and references
+    Export {
+        /// Output format: md, json, jsonld, skos or dot
+        #[arg(long, default_value = "md")]
+        format: instruction_files::ExportFormat,
+
+        /// Project root (default: auto-detect from CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Ontology directory; repeat for multiple sources (default: same as audit)
+        #[arg(long)]
+        ontology_dir: Vec<PathBuf>,
+    },
+
+    /// Regenerate the glossary between ontology-glossary markers
+    Glossary {
+        /// Project root (default: auto-detect from CWD)
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Ontology directory; repeat for multiple sources (default: same as audit)
+        #[arg(long)]
+        ontology_dir: Vec<PathBuf>,
+
+        /// Report out-of-date glossaries instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
 }
 
 fn main() -> Result<()> {
@@ -50,17 +164,66 @@ fn main() -> Result<()> {
             root,
             broad: _,
             ontology_dir,
+            check_urls,
+            fix,
+            strict_term_case,
         } => {
             let config = AuditConfig::agent_doc();
+            let options = RunOptions {
+                check_urls,
+                fix,
+                strict_term_case,
+            };
             #[cfg(feature = "ontology")]
             {
                 let _ = &ontology_dir;
-                instruction_files::run(&config, root.as_deref(), ontology_dir.as_deref())?;
+                instruction_files::run_with_options(
+                    &config,
+                    root.as_deref(),
+                    &options,
+                    &ontology_dir,
+                )?;
             }
             #[cfg(not(feature = "ontology"))]
             {
                 let _ = &ontology_dir;
-                instruction_files::run(&config, root.as_deref())?;
+                instruction_files::run_with_options(&config, root.as_deref(), &options)?;
+            }
+        }
+        Commands::Context { path, root, concat } => {
+            let config = AuditConfig::agent_doc();
+            let project_root = match root {
+                Some(r) => r,
+                None => instruction_files::find_root(&config),
+            };
+            let target = instruction_files::relative_to_root(&project_root, &path)?;
+            let entries = instruction_files::effective_context(&project_root, &target, &config);
+            if concat {
+                let merged: String = entries.iter().map(|e| e.content.as_str()).collect();
+                print!("{}", merged);
+            } else if entries.is_empty() {
+                println!("No instruction files apply to {}.", target.display());
+            } else {
+                for e in &entries {
+                    let name = match &e.source {
+                        ContextSource::Import { from } => {
+                            format!("{} (imported by {})", e.path.display(), from.display())
+                        }
+                        ContextSource::Rule { pattern } => {
+                            format!("{} (rule: {})", e.path.display(), pattern)
+                        }
+                        _ => e.path.display().to_string(),
+                    };
+                    println!("  {:<60} {:>5} lines {:>7} tokens", name, e.lines, e.tokens);
+                }
+                let lines: usize = entries.iter().map(|e| e.lines).sum();
+                let tokens: usize = entries.iter().map(|e| e.tokens).sum();
+                println!(
+                    "\nTotal: {} lines, ~{} tokens ({} file(s))",
+                    lines,
+                    tokens,
+                    entries.len()
+                );
             }
         }
         Commands::Init { root } => {
@@ -92,6 +255,54 @@ fn main() -> Result<()> {
                 println!("\n{} file(s) found.", files.len());
             }
         }
+        Commands::VerifySnippets { root } => {
+            let config = AuditConfig::agent_doc();
+            instruction_files::run_verify(&config, root.as_deref())?;
+        }
+        Commands::ExtractRunbook {
+            file,
+            section,
+            root,
+            min_lines,
+        } => {
+            let config = AuditConfig::agent_doc();
+            instruction_files::run_extract_runbook(
+                &config,
+                root.as_deref(),
+                &file,
+                section.as_deref(),
+                min_lines,
+            )?;
+        }
+        Commands::Runbooks { command } => match command {
+            RunbookCommands::Upgrade { root, check } => {
+                let root = root.unwrap_or_else(|| PathBuf::from("."));
+                instruction_files::run_runbooks_upgrade(&root, check)?;
+            }
+        },
+        #[cfg(feature = "ontology")]
+        Commands::Ontology { command } => {
+            let config = AuditConfig::agent_doc();
+            match command {
+                OntologyCommands::Export {
+                    format,
+                    root,
+                    ontology_dir,
+                } => instruction_files::run_ontology_export(
+                    &config,
+                    root.as_deref(),
+                    &ontology_dir,
+                    format,
+                )?,
+                OntologyCommands::Glossary {
+                    root,
+                    ontology_dir,
+                    check,
+                } => {
+                    instruction_files::run_glossary(&config, root.as_deref(), &ontology_dir, check)?
+                }
+            }
+        }
     }
 
     Ok(())
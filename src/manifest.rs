@@ -0,0 +1,643 @@
+//! Manifest consistency checks for instruction files.
+//!
+//! Compares crate/package names, versions and feature names quoted in
+//! instruction files against the project's `Cargo.toml`, `package.json`
+//! and `pyproject.toml`, so install snippets don't drift from the manifest.
+
+use crate::commands::{SHELL_LANGS, find_up, split_commands};
+use crate::markdown::{code_blocks, code_spans, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+static TOML_DEP_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*([A-Za-z0-9_-]+)\s*=\s*(".*"|\{.*\})\s*(?:#.*)?$"#).unwrap());
+static TOML_VERSION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"version\s*=\s*"([^"]*)""#).unwrap());
+static TOML_FEATURES_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"features\s*=\s*\[([^\]]*)\]"#).unwrap());
+static JSON_DEP_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*\{?\s*"([@A-Za-z0-9_./-]+)"\s*:\s*"([^"]*)""#).unwrap());
+static QUOTED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""([^"]*)""#).unwrap());
+static PIP_SPEC_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([A-Za-z0-9_.-]+)(?:\[([^\]]*)\])?(?:==([0-9][^\s,;]*))?$").unwrap()
+});
+
+/// Package metadata from one manifest file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Manifest file name relative to the project root (e.g. `Cargo.toml`).
+    pub file: String,
+    pub name: String,
+    pub version: String,
+    /// Cargo features, or Python extras.
+    pub features: Vec<String>,
+    /// Dependency name -> version requirement.
+    pub dependencies: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Parse a `Cargo.toml`.
+    pub fn from_cargo_toml(file: &str, content: &str) -> Option<Self> {
+        let table: toml::Table = content.parse().ok()?;
+        let package = table.get("package")?.as_table()?;
+        let mut m = Manifest {
+            file: file.to_string(),
+            name: package.get("name")?.as_str()?.to_string(),
+            version: package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            ..Default::default()
+        };
+        if let Some(features) = table.get("features").and_then(|f| f.as_table()) {
+            m.features.extend(features.keys().cloned());
+        }
+        for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            let Some(deps) = table.get(section).and_then(|d| d.as_table()) else {
+                continue;
+            };
+            for (name, spec) in deps {
+                let (req, optional) = match spec {
+                    toml::Value::String(v) => (Some(v.as_str()), false),
+                    toml::Value::Table(t) => (
+                        t.get("version").and_then(|v| v.as_str()),
+                        t.get("optional").and_then(|o| o.as_bool()).unwrap_or(false),
+                    ),
+                    _ => (None, false),
+                };
+                if optional && !m.features.contains(name) {
+                    m.features.push(name.clone());
+                }
+                if let Some(req) = req {
+                    m.dependencies.insert(name.clone(), req.to_string());
+                }
+            }
+        }
+        Some(m)
+    }
+
+    /// Parse a `package.json`.
+    pub fn from_package_json(file: &str, content: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(content).ok()?;
+        let mut m = Manifest {
+            file: file.to_string(),
+            name: json.get("name")?.as_str()?.to_string(),
+            version: json
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            ..Default::default()
+        };
+        for section in ["dependencies", "devDependencies", "peerDependencies"] {
+            if let Some(deps) = json.get(section).and_then(|d| d.as_object()) {
+                for (name, req) in deps {
+                    if let Some(req) = req.as_str() {
+                        m.dependencies.insert(name.clone(), req.to_string());
+                    }
+                }
+            }
+        }
+        Some(m)
+    }
+
+    /// Parse a `pyproject.toml` (`[project]` table, PEP 621).
+    pub fn from_pyproject(file: &str, content: &str) -> Option<Self> {
+        let table: toml::Table = content.parse().ok()?;
+        let project = table.get("project")?.as_table()?;
+        let mut m = Manifest {
+            file: file.to_string(),
+            name: project.get("name")?.as_str()?.to_string(),
+            version: project
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            ..Default::default()
+        };
+        if let Some(extras) = project
+            .get("optional-dependencies")
+            .and_then(|o| o.as_table())
+        {
+            m.features.extend(extras.keys().cloned());
+        }
+        Some(m)
+    }
+}
+
+type ManifestParser = fn(&str, &str) -> Option<Manifest>;
+
+/// Load the manifests nearest to `dir` (relative to `root`).
+pub fn load_manifests(root: &Path, dir: &Path) -> Vec<Manifest> {
+    let loaders: [(&str, ManifestParser); 3] = [
+        ("Cargo.toml", Manifest::from_cargo_toml),
+        ("package.json", Manifest::from_package_json),
+        ("pyproject.toml", Manifest::from_pyproject),
+    ];
+    let mut out = Vec::new();
+    for (name, parse) in loaders {
+        if let Some(path) = find_up(root, dir, &[name])
+            && let Ok(content) = std::fs::read_to_string(&path)
+        {
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy();
+            if let Some(m) = parse(&rel, &content) {
+                out.push(m);
+            }
+        }
+    }
+    out
+}
+
+/// Leading numeric components of a version or requirement (`^1.2` -> [1, 2]).
+fn version_parts(v: &str) -> Vec<u64> {
+    v.trim_start_matches(|c: char| !c.is_ascii_digit())
+        .split(['.', '-', '+'])
+        .map_while(|p| p.parse().ok())
+        .take(3)
+        .collect()
+}
+
+/// Whether `version` satisfies a requirement `req` (caret unless another
+/// operator is given).
+///
+/// `"0.2"` accepts `0.2.x`, `"1"` accepts `1.x.y`, `"0.2.3"` accepts
+/// `>=0.2.3, <0.3.0`. `~1.2` accepts `1.2.x` and `~1` accepts `1.x.y`.
+/// `==` pins must match exactly on the given components; `>=` and `>`
+/// compare. Wildcards, ranges, other operators and unparseable
+/// requirements are accepted.
+fn satisfies(req: &str, version: &str) -> bool {
+    let req = req.trim();
+    if req.is_empty()
+        || req == "*"
+        || req.contains(['*', 'x', '|', ',', '<'])
+        || req.starts_with("~=")
+        || req.starts_with("~>")
+    {
+        return true;
+    }
+    let want = version_parts(req);
+    let have = version_parts(version);
+    if want.is_empty() || have.is_empty() {
+        return true;
+    }
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    if req.starts_with('=') {
+        return (0..want.len()).all(|i| at(&want, i) == at(&have, i));
+    }
+    if req.starts_with(">=") {
+        return (0..3)
+            .map(|i| at(&have, i))
+            .ge((0..3).map(|i| at(&want, i)));
+    }
+    if req.starts_with('>') {
+        return (0..3)
+            .map(|i| at(&have, i))
+            .gt((0..3).map(|i| at(&want, i)));
+    }
+    // Tilde: the major and, when given, the minor component must match.
+    // Caret: the first non-zero component (or the last given) must match.
+    let pinned = if req.starts_with('~') {
+        want.len().min(2) - 1
+    } else {
+        want.iter().position(|&n| n != 0).unwrap_or(want.len() - 1)
+    };
+    if (0..=pinned).any(|i| at(&want, i) != at(&have, i)) {
+        return false;
+    }
+    (0..3)
+        .map(|i| at(&have, i))
+        .ge((0..3).map(|i| at(&want, i)))
+}
+
+/// A quoted package reference found in an instruction file.
+struct Mention {
+    line: usize,
+    name: String,
+    version: Option<String>,
+    features: Vec<String>,
+}
+
+fn quoted_list(list: &str) -> Vec<String> {
+    QUOTED_RE
+        .captures_iter(list)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+/// `name = "ver"` / `name = { version = "ver", features = [...] }` lines.
+fn toml_mention(line: usize, text: &str) -> Option<Mention> {
+    let cap = TOML_DEP_RE.captures(text)?;
+    let value = &cap[2];
+    let (version, features) = if value.starts_with('"') {
+        (Some(value.trim_matches('"').to_string()), Vec::new())
+    } else {
+        (
+            TOML_VERSION_RE.captures(value).map(|c| c[1].to_string()),
+            TOML_FEATURES_RE
+                .captures(value)
+                .map(|c| quoted_list(&c[1]))
+                .unwrap_or_default(),
+        )
+    };
+    Some(Mention {
+        line,
+        name: cap[1].to_string(),
+        version,
+        features,
+    })
+}
+
+/// `cargo add name@ver --features a,b`, `npm install name@ver`,
+/// `pip install "name[extra]==ver"`.
+fn command_mentions(line: usize, command: &str) -> Vec<Mention> {
+    let tokens: Vec<&str> = command
+        .split_whitespace()
+        .map(|t| t.trim_matches(['"', '\'']))
+        .collect();
+    let (tool, sub) = match tokens.as_slice() {
+        [tool, sub, ..] => (*tool, *sub),
+        _ => return Vec::new(),
+    };
+    let mut mentions = Vec::new();
+    match (tool, sub) {
+        ("cargo", "add" | "install") => {
+            let mut features = Vec::new();
+            let mut names = Vec::new();
+            let mut iter = tokens[2..].iter();
+            while let Some(t) = iter.next() {
+                if matches!(*t, "--features" | "-F") {
+                    if let Some(list) = iter.next() {
+                        features.extend(list.split([',', ' ']).map(str::to_string));
+                    }
+                } else if let Some(list) = t.strip_prefix("--features=") {
+                    features.extend(list.split(',').map(str::to_string));
+                } else if !t.is_empty() && !t.starts_with('-') {
+                    names.push(*t);
+                }
+            }
+            for spec in names {
+                let (name, version) = match spec.split_once('@') {
+                    Some((n, v)) => (n, Some(v.to_string())),
+                    None => (spec, None),
+                };
+                mentions.push(Mention {
+                    line,
+                    name: name.to_string(),
+                    version,
+                    features: features.clone(),
+                });
+            }
+        }
+        ("npm" | "pnpm" | "yarn" | "bun", "install" | "i" | "add") => {
+            for spec in tokens[2..]
+                .iter()
+                .filter(|t| !t.is_empty() && !t.starts_with('-'))
+            {
+                // Scoped packages start with '@'; the version follows the last '@'.
+                let unscoped = spec.strip_prefix('@').unwrap_or(spec);
+                let scope = spec.len() - unscoped.len();
+                let (name, version) = match unscoped.rfind('@') {
+                    Some(i) => (&spec[..scope + i], Some(unscoped[i + 1..].to_string())),
+                    None => (*spec, None),
+                };
+                mentions.push(Mention {
+                    line,
+                    name: name.to_string(),
+                    version,
+                    features: Vec::new(),
+                });
+            }
+        }
+        ("pip" | "pip3" | "uv", "install" | "add") => {
+            for spec in tokens[2..].iter().filter(|t| !t.starts_with('-')) {
+                if let Some(c) = PIP_SPEC_RE.captures(spec) {
+                    mentions.push(Mention {
+                        line,
+                        name: c[1].to_string(),
+                        version: c.get(3).map(|v| format!("=={}", v.as_str())),
+                        features: c
+                            .get(2)
+                            .map(|e| {
+                                e.as_str()
+                                    .split(',')
+                                    .map(|s| s.trim().to_string())
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+    mentions
+}
+
+/// Collect package mentions from TOML/JSON code blocks, shell blocks and
+/// inline code.
+fn extract_mentions(content: &str) -> Vec<Mention> {
+    let mut mentions = Vec::new();
+    for block in code_blocks(content) {
+        let lang = block.lang();
+        for (line, text) in &block.lines {
+            match lang.as_str() {
+                "toml" => mentions.extend(toml_mention(*line, text)),
+                "json" | "jsonc" => {
+                    if let Some(c) = JSON_DEP_RE.captures(text) {
+                        mentions.push(Mention {
+                            line: *line,
+                            name: c[1].to_string(),
+                            version: Some(c[2].to_string()),
+                            features: Vec::new(),
+                        });
+                    }
+                }
+                l if SHELL_LANGS.contains(&l) => {
+                    let text = text.trim_start();
+                    let text = text.strip_prefix("$ ").unwrap_or(text);
+                    for cmd in split_commands(text) {
+                        mentions.extend(command_mentions(*line, cmd));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    for (line, text) in prose_lines(content) {
+        for span in code_spans(text) {
+            if let Some(m) = toml_mention(line, &span) {
+                mentions.push(m);
+                continue;
+            }
+            for cmd in split_commands(span.strip_prefix("$ ").unwrap_or(&span)) {
+                mentions.extend(command_mentions(line, cmd));
+            }
+        }
+    }
+    mentions.sort_by_key(|m| m.line);
+    mentions
+}
+
+/// Check package names, versions and features quoted in an instruction file
+/// against the nearest `Cargo.toml`, `package.json` and `pyproject.toml`.
+///
+/// For the project's own package, quoted versions must be satisfied by the
+/// manifest version and quoted features must exist. For the project's
+/// dependencies, quoted versions must be compatible with the manifest's
+/// requirement. Names the manifests don't know about are ignored.
+pub fn check_manifest_refs(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let dir = Path::new(rel).parent().unwrap_or(Path::new(""));
+    let manifests = load_manifests(root, dir);
+    if manifests.is_empty() {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    for mention in extract_mentions(content) {
+        let mut push = |message: String| {
+            issues.push(Issue {
+                file: rel.to_string(),
+                line: mention.line,
+                end_line: 0,
+                message,
+                warning: false,
+            })
+        };
+        for m in &manifests {
+            if mention.name == m.name {
+                if let Some(v) = &mention.version
+                    && !m.version.is_empty()
+                    && !satisfies(v, &m.version)
+                {
+                    push(format!(
+                        "Version \"{}\" for `{}` doesn't match {} ({})",
+                        v, m.name, m.file, m.version
+                    ));
+                }
+                for f in mention.features.iter().filter(|f| !f.is_empty()) {
+                    if !m.features.contains(f) {
+                        push(format!(
+                            "Unknown feature `{}` for `{}` \u{2014} not in {}",
+                            f, m.name, m.file
+                        ));
+                    }
+                }
+            } else if let Some(req) = m.dependencies.get(&mention.name)
+                && let Some(v) = &mention.version
+            {
+                // Two requirements agree when either one's minimum satisfies the other.
+                let base = |r: &str| {
+                    version_parts(r)
+                        .iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>()
+                        .join(".")
+                };
+                if !satisfies(v, &base(req)) && !satisfies(req, &base(v)) {
+                    push(format!(
+                        "Version \"{}\" for `{}` doesn't match {} (\"{}\")",
+                        v, mention.name, m.file, req
+                    ));
+                }
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const CARGO_TOML: &str = r#"
+[package]
+name = "instruction-files"
+version = "0.2.2"
+
+[dependencies]
+agent-kit = "0.4"
+existence = { version = "0.4", optional = true }
+
+[features]
+ontology = ["existence"]
+spec-audit = []
+"#;
+
+    fn cargo_project() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("Cargo.toml"), CARGO_TOML).unwrap();
+        tmp
+    }
+
+    #[test]
+    fn satisfies_caret_and_pins() {
+        assert!(satisfies("0.2", "0.2.2"));
+        assert!(satisfies("^0.2.1", "0.2.2"));
+        assert!(!satisfies("0.1", "0.2.2"));
+        assert!(!satisfies("0.2.5", "0.2.2"));
+        assert!(satisfies("1", "1.4.0"));
+        assert!(!satisfies("1", "2.0.0"));
+        assert!(satisfies("==0.2.2", "0.2.2"));
+        assert!(!satisfies("==0.2.1", "0.2.2"));
+        assert!(satisfies(">=0.1", "0.2.2"));
+        assert!(satisfies("*", "0.2.2"));
+        assert!(satisfies("~1.2", "1.2.9"));
+        assert!(!satisfies("~1.2", "1.3.0"));
+        assert!(satisfies("~1.2.3", "1.2.4"));
+        assert!(!satisfies("~1.2.3", "1.2.2"));
+        assert!(satisfies("~1", "1.9.0"));
+        assert!(!satisfies("~1", "2.0.0"));
+        assert!(satisfies(">1.2", "1.2.1"));
+        assert!(!satisfies(">1.2", "1.2.0"));
+        assert!(!satisfies(">1.2", "1.1.9"));
+        assert!(satisfies("~=1.4", "1.9.0"));
+    }
+
+    #[test]
+    fn cargo_manifest_features_include_optional_deps() {
+        let m = Manifest::from_cargo_toml("Cargo.toml", CARGO_TOML).unwrap();
+        assert_eq!(m.name, "instruction-files");
+        assert!(m.features.contains(&"ontology".to_string()));
+        assert!(m.features.contains(&"existence".to_string()));
+        assert_eq!(m.dependencies["agent-kit"], "0.4");
+    }
+
+    #[test]
+    fn matching_snippets_pass() {
+        let tmp = cargo_project();
+        let content = "\
+```toml
+[dependencies]
+instruction-files = \"0.2\"
+instruction-files = { version = \"0.2\", features = [\"ontology\", \"spec-audit\"] }
+agent-kit = \"0.4\"
+tempfile = \"3\"
+```
+
+Or run `cargo add instruction-files --features ontology`.
+";
+        let issues = check_manifest_refs("README.md", content, tmp.path());
+        assert!(
+            issues.is_empty(),
+            "unexpected: {:?}",
+            issues.iter().map(|i| &i.message).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn drifted_version_and_feature_reported_with_lines() {
+        let tmp = cargo_project();
+        let content = "\
+# Install
+
+```toml
+instruction-files = { version = \"0.1\", features = [\"ontology\", \"urls\"] }
+agent-kit = \"0.3\"
+```
+
+`cargo add instruction-files@0.3 -F spec-audit`
+";
+        let issues = check_manifest_refs("AGENTS.md", content, tmp.path());
+        let found: Vec<(usize, &str)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    4,
+                    "Version \"0.1\" for `instruction-files` doesn't match Cargo.toml (0.2.2)"
+                ),
+                (
+                    4,
+                    "Unknown feature `urls` for `instruction-files` \u{2014} not in Cargo.toml"
+                ),
+                (
+                    5,
+                    "Version \"0.3\" for `agent-kit` doesn't match Cargo.toml (\"0.4\")"
+                ),
+                (
+                    8,
+                    "Version \"0.3\" for `instruction-files` doesn't match Cargo.toml (0.2.2)"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_json_and_pyproject() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "@acme/kit", "version": "2.1.0", "dependencies": {"zod": "^3.22.0"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"acme-kit\"\nversion = \"1.0.0\"\n[project.optional-dependencies]\ncli = []\n",
+        )
+        .unwrap();
+        let content = "\
+```sh
+npm install @acme/kit@1.9.0
+pip install \"acme-kit[cli,gui]==1.0.0\"
+```
+
+```json
+{ \"zod\": \"^3.23.0\" }
+{ \"zod\": \"^2.0.0\" }
+```
+";
+        let issues = check_manifest_refs("AGENTS.md", content, root);
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Version \"1.9.0\" for `@acme/kit` doesn't match package.json (2.1.0)",
+                "Unknown feature `gui` for `acme-kit` \u{2014} not in pyproject.toml",
+                "Version \"^2.0.0\" for `zod` doesn't match package.json (\"^3.22.0\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn npm_specs_with_multibyte_or_empty_tokens() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "app", "dependencies": {"zod": "^3.22.0"}}"#,
+        )
+        .unwrap();
+        let content =
+            "`npm install \u{e9}`\n`npm install \"\"`\n`npm install @\u{e9}`\n`npm i zod@^2.0.0`\n";
+        let issues = check_manifest_refs("AGENTS.md", content, root);
+        let found: Vec<(usize, &str)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(
+                4,
+                "Version \"^2.0.0\" for `zod` doesn't match package.json (\"^3.22.0\")"
+            )]
+        );
+        assert!(command_mentions(1, "npm install \"\"").is_empty());
+    }
+
+    #[test]
+    fn no_manifest_returns_empty() {
+        let tmp = TempDir::new().unwrap();
+        let issues = check_manifest_refs("AGENTS.md", "`cargo add foo@1`\n", tmp.path());
+        assert!(issues.is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+//! Shared markdown scanning helpers used by the instruction file checks.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// A fenced code block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CodeBlock<'a> {
+    /// Info string after the opening fence (e.g. `sh verify`).
+    pub info: &'a str,
+    /// 1-based line number of the opening fence.
+    pub fence_line: usize,
+    /// 1-based line number of the closing fence (last line for unclosed blocks).
+    pub close_line: usize,
+    /// `(line_number, line)` for each line inside the block.
+    pub lines: Vec<(usize, &'a str)>,
+}
+
+impl CodeBlock<'_> {
+    /// Language tag: the first word of the info string, lowercased.
+    pub fn lang(&self) -> String {
+        self.info
+            .split(|c: char| c.is_whitespace() || c == '{' || c == ',')
+            .next()
+            .unwrap_or("")
+            .to_lowercase()
+    }
+}
+
+/// Opening fence: indentation, then three or more backticks or tildes.
+fn open_fence(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let n = trimmed.chars().take_while(|&c| c == ch).count();
+    if n < 3 {
+        return None;
+    }
+    let info = trimmed[n..].trim();
+    if ch == '`' && info.contains('`') {
+        return None;
+    }
+    Some((ch, n, info))
+}
+
+/// Extract fenced code blocks (```` ``` ```` or `~~~`, CommonMark-style).
+pub(crate) fn code_blocks(content: &str) -> Vec<CodeBlock<'_>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(char, usize, CodeBlock)> = None;
+    let mut last = 0;
+    for (i, line) in content.lines().enumerate() {
+        last = i + 1;
+        match current.take() {
+            None => {
+                if let Some((ch, n, info)) = open_fence(line) {
+                    let block = CodeBlock {
+                        info,
+                        fence_line: i + 1,
+                        close_line: 0,
+                        lines: Vec::new(),
+                    };
+                    current = Some((ch, n, block));
+                }
+            }
+            Some((ch, n, mut block)) => {
+                let t = line.trim();
+                if t.len() >= n && t.chars().all(|c| c == ch) {
+                    block.close_line = i + 1;
+                    blocks.push(block);
+                } else {
+                    block.lines.push((i + 1, line));
+                    current = Some((ch, n, block));
+                }
+            }
+        }
+    }
+    if let Some((_, _, mut block)) = current {
+        block.close_line = last;
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Return `(line_number, line)` pairs for every line outside fenced code blocks.
+///
+/// Line numbers are 1-based. Fence delimiter lines themselves are skipped.
+pub(crate) fn prose_lines(content: &str) -> Vec<(usize, &str)> {
+    let blocks = code_blocks(content);
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(n, _)| {
+            !blocks
+                .iter()
+                .any(|b| (b.fence_line..=b.close_line).contains(n))
+        })
+        .collect()
+}
+
+/// Char ranges of inline code spans in `chars`, backticks included.
+fn code_span_ranges(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '`' {
+            i += 1;
+            continue;
+        }
+        let run = chars[i..].iter().take_while(|&&c| c == '`').count();
+        let mut j = i + run;
+        let mut close = None;
+        while j < chars.len() {
+            if chars[j] == '`' {
+                let n = chars[j..].iter().take_while(|&&c| c == '`').count();
+                if n == run {
+                    close = Some(j + n);
+                    break;
+                }
+                j += n;
+            } else {
+                j += 1;
+            }
+        }
+        match close {
+            Some(end) => {
+                ranges.push((i, end));
+                i = end;
+            }
+            None => i += run,
+        }
+    }
+    ranges
+}
+
+/// Replace inline code spans (`` `like this` ``) with spaces.
+///
+/// The character count of the line is preserved so positions found in the
+/// masked line still line up with the original. Unterminated spans are left
+/// untouched, matching CommonMark.
+pub(crate) fn mask_code_spans(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = chars.clone();
+    for (start, end) in code_span_ranges(&chars) {
+        for c in &mut out[start..end] {
+            *c = ' ';
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Contents of the inline code spans in `line`, trimmed.
+pub(crate) fn code_spans(line: &str) -> Vec<String> {
+    code_spans_at(line).into_iter().map(|(_, s)| s).collect()
+}
+
+/// [`code_spans`] with the byte offset of each span's opening backtick.
+pub(crate) fn code_spans_at(line: &str) -> Vec<(usize, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    code_span_ranges(&chars)
+        .into_iter()
+        .map(|(start, end)| {
+            let run = chars[start..].iter().take_while(|&&c| c == '`').count();
+            let offset = chars[..start].iter().map(|c| c.len_utf8()).sum();
+            let text = chars[start + run..end - run]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string();
+            (offset, text)
+        })
+        .collect()
+}
+
+/// GitHub-style heading anchor slug: lowercase, punctuation dropped,
+/// spaces turned into hyphens.
+pub(crate) fn slugify(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// ATX headings outside fenced code blocks as `(line_number, level, text)`.
+pub(crate) fn headings(content: &str) -> Vec<(usize, usize, &str)> {
+    prose_lines(content)
+        .into_iter()
+        .filter_map(|(n, line)| {
+            let level = line.chars().take_while(|&c| c == '#').count();
+            if level == 0 || level > 6 {
+                return None;
+            }
+            let rest = &line[level..];
+            if !rest.is_empty() && !rest.starts_with(' ') {
+                return None;
+            }
+            Some((n, level, rest.trim().trim_end_matches('#').trim_end()))
+        })
+        .collect()
+}
+
+/// Anchors a link fragment can target in `content`: heading slugs (with
+/// GitHub's `-1`, `-2` suffixes for duplicates) plus explicit HTML
+/// `id`/`name` attributes.
+pub(crate) fn anchors(content: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for (_, _, text) in headings(content) {
+        let base = slugify(text);
+        let mut slug = base.clone();
+        let mut n = 0;
+        while out.contains(&slug) {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+        out.push(slug);
+    }
+    for cap in HTML_ANCHOR_RE.captures_iter(content) {
+        out.push(cap[1].to_string());
+    }
+    out
+}
+
+static HTML_ANCHOR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<[a-zA-Z][^>]*\s(?:id|name)="([^"]+)""#).unwrap());
+
+/// A single `key: value` entry in YAML front matter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FrontMatterField {
+    pub key: String,
+    /// Scalar value with surrounding quotes removed (empty for block lists).
+    pub value: String,
+    /// Items of a block list (`- item` lines following the key).
+    pub items: Vec<String>,
+    /// 1-based line number of the key.
+    pub line: usize,
+}
+
+/// YAML front matter delimited by `---` lines at the top of a file.
+///
+/// Only the subset instruction files use is understood: `key: value`
+/// scalars, inline lists (`[a, b]`) and block lists (`- a`).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct FrontMatter {
+    pub fields: Vec<FrontMatterField>,
+    /// 1-based line number of the closing `---`.
+    pub end_line: usize,
+}
+
+impl FrontMatter {
+    pub fn field(&self, key: &str) -> Option<&FrontMatterField> {
+        self.fields.iter().find(|f| f.key == key)
+    }
+
+    /// List value for `key`: block list items, an inline `[a, b]` list, or a
+    /// comma-separated scalar.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        let Some(field) = self.field(key) else {
+            return Vec::new();
+        };
+        if !field.items.is_empty() {
+            return field.items.clone();
+        }
+        let inner = field
+            .value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .unwrap_or(&field.value);
+        inner
+            .split(',')
+            .map(unquote)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| s.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(s)
+}
+
+/// Parse front matter at the top of `content`.
+///
+/// Returns `None` when the file does not start with `---` or the block is
+/// never closed.
+pub(crate) fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    let mut lines = content.lines().enumerate();
+    let (_, first) = lines.next()?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+    let mut fm = FrontMatter::default();
+    for (i, line) in lines {
+        if line.trim_end() == "---" {
+            fm.end_line = i + 1;
+            return Some(fm);
+        }
+        if let Some(item) = line.trim_start().strip_prefix("- ")
+            && line.starts_with([' ', '-'])
+            && let Some(last) = fm.fields.last_mut()
+        {
+            last.items.push(unquote(item).to_string());
+            continue;
+        }
+        if line.starts_with([' ', '\t', '#']) || line.trim().is_empty() {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            fm.fields.push(FrontMatterField {
+                key: key.trim().to_string(),
+                value: unquote(value).to_string(),
+                items: Vec::new(),
+                line: i + 1,
+            });
+        }
+    }
+    None
+}
+
+/// Return `content` with any front matter block removed.
+pub(crate) fn strip_front_matter(content: &str) -> &str {
+    match parse_front_matter(content) {
+        Some(fm) => {
+            let mut offset = 0;
+            for (i, line) in content.split_inclusive('\n').enumerate() {
+                offset += line.len();
+                if i + 1 == fm.end_line {
+                    break;
+                }
+            }
+            &content[offset..]
+        }
+        None => content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prose_lines_skips_fences() {
+        let content = "a\n```\nb\n```\nc\n";
+        let lines = prose_lines(content);
+        assert_eq!(lines, vec![(1, "a"), (5, "c")]);
+    }
+
+    #[test]
+    fn prose_lines_handles_tildes_and_long_fences() {
+        let content = "a\n~~~\n```\n~~~\nb\n````md\n```\n````\nc\n";
+        assert_eq!(prose_lines(content), vec![(1, "a"), (5, "b"), (9, "c")]);
+    }
+
+    #[test]
+    fn code_blocks_info_and_lines() {
+        let content = "# T\n\n```sh verify\nmake test\n```\n\n```\nunclosed\n";
+        let blocks = code_blocks(content);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].info, "sh verify");
+        assert_eq!(blocks[0].lang(), "sh");
+        assert_eq!(blocks[0].fence_line, 3);
+        assert_eq!(blocks[0].close_line, 5);
+        assert_eq!(blocks[0].lines, vec![(4, "make test")]);
+        assert_eq!(blocks[1].lang(), "");
+        assert_eq!(blocks[1].close_line, 8);
+    }
+
+    #[test]
+    fn code_spans_extracts_contents() {
+        assert_eq!(
+            code_spans("run `make test` or `` npm run `x` ``"),
+            vec!["make test", "npm run `x`"]
+        );
+        assert_eq!(
+            code_spans_at("caf\u{e9} `a` then `a`"),
+            vec![(6, "a".to_string()), (15, "a".to_string())]
+        );
+    }
+
+    #[test]
+    fn mask_code_spans_preserves_length() {
+        let masked = mask_code_spans("see `@foo` and ``a`b`` here");
+        assert_eq!(
+            masked.chars().count(),
+            "see `@foo` and ``a`b`` here".chars().count()
+        );
+        assert!(!masked.contains("@foo"));
+        assert!(!masked.contains("a`b"));
+        assert!(masked.ends_with(" here"));
+    }
+
+    #[test]
+    fn mask_code_spans_unterminated() {
+        assert_eq!(mask_code_spans("a `b c"), "a `b c");
+    }
+
+    #[test]
+    fn anchors_follow_github_slugs() {
+        let content = "\
+# Release Process
+## Steps (v2)
+## Steps (v2)
+```
+# not a heading
+```
+<a name=\"custom\"></a>
+";
+        assert_eq!(
+            anchors(content),
+            vec!["release-process", "steps-v2", "steps-v2-1", "custom"]
+        );
+    }
+
+    #[test]
+    fn parse_front_matter_scalars_and_lists() {
+        let content = "\
+---
+name: demo
+description: \"A demo\"
+aliases: [ctx, 'context-window']
+paths:
+  - src/**/*.rs
+  - tests/*.rs
+---
+# Body
+";
+        let fm = parse_front_matter(content).unwrap();
+        assert_eq!(fm.field("name").unwrap().value, "demo");
+        assert_eq!(fm.field("description").unwrap().value, "A demo");
+        assert_eq!(fm.list("aliases"), vec!["ctx", "context-window"]);
+        assert_eq!(fm.list("paths"), vec!["src/**/*.rs", "tests/*.rs"]);
+        assert_eq!(fm.field("paths").unwrap().line, 5);
+        assert_eq!(fm.end_line, 8);
+        assert_eq!(strip_front_matter(content), "# Body\n");
+    }
+
+    #[test]
+    fn parse_front_matter_absent_or_unclosed() {
+        assert!(parse_front_matter("# Title\n").is_none());
+        assert!(parse_front_matter("---\nname: x\n").is_none());
+        assert_eq!(strip_front_matter("# Title\n"), "# Title\n");
+    }
+}
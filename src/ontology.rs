@@ -1,50 +1,952 @@
 //! Ontology term validation for instruction files.
 //!
 //! Scans markdown content for `[term:Name]` annotations and verifies
-//! that each referenced term has a corresponding `.md` file in the
-//! ontology directory.
+//! that each referenced term has a corresponding `.md` file in one of the
+//! ontology sources, or is one of the `aliases` declared in a term file's
+//! front matter. Namespaced annotations (`[term:billing/Invoice]`) resolve
+//! to nested files (`src/billing/invoice.md`). The coverage report goes the
+//! other way: term files that no instruction file annotates.
+//!
+//! Sources are ontology directories listed in order of precedence: when two
+//! define the same term, the first wins and the collision is reported. They
+//! come from `--ontology-dir`, `[ontology] dirs` or an `existence.toml`
+//! manifest (see [`resolve_ontology_dirs`]).
 
+use crate::glossary::glossary_lines;
+use crate::markdown::{
+    headings, mask_code_spans, parse_front_matter, prose_lines, strip_front_matter,
+};
 use crate::types::Issue;
+use once_cell::sync::Lazy;
 use regex::Regex;
-use std::path::Path;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+pub(crate) static TERM_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[term:([A-Za-z][A-Za-z0-9_-]*(?:/[A-Za-z][A-Za-z0-9_-]*)*)\]")
+        .expect("valid regex")
+});
+static MD_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\]\(([^)\s#]+\.md)(?:#[^)]*)?\)").expect("valid regex"));
+/// Prose that shouldn't get annotations: term annotations, links, URLs and
+/// HTML.
+static NO_MENTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[term:[^\]]*\]|!?\[[^\]]*\]\([^)]*\)|\[[^\]]*\]\[[^\]]*\]|https?://\S+|<[^>]*>")
+        .expect("valid regex")
+});
+static ANY_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[[^\]]*\]\(([^)\s]+)\)").expect("valid regex"));
+
+/// mdBook table of contents; its links count, but it isn't a term.
+const SUMMARY: &str = "SUMMARY.md";
+/// Manifest that marks an `existence` ontology directory.
+pub const EXISTENCE_MANIFEST: &str = "existence.toml";
+
+/// How annotation spelling is compared with term names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TermCase {
+    /// `[term:context]` resolves `Context` silently.
+    #[default]
+    Insensitive,
+    /// Annotations that resolve only case-insensitively are warnings.
+    Strict,
+}
+
+/// A term file in the ontology.
+#[derive(Debug, Clone)]
+pub struct Term {
+    /// Canonical spelling: the namespace directories followed by the
+    /// `# Title` (spaces as `-`) when it matches the file name, otherwise
+    /// the file stem (`billing/Invoice`).
+    pub name: String,
+    pub path: PathBuf,
+    /// Ontology directory the term was loaded from.
+    pub source: PathBuf,
+    /// Alternative spellings from the `aliases` front matter list.
+    pub aliases: Vec<String>,
+    /// Broader terms from the `is_a` front matter list.
+    pub is_a: Vec<String>,
+}
+
+/// Every term and alias of a list of ontology sources, looked up
+/// case-insensitively.
+#[derive(Debug, Default)]
+pub struct TermIndex {
+    /// Keyed by lowercase path under `src/` without `.md` (`billing/invoice`).
+    terms: BTreeMap<String, Term>,
+    /// Lowercase alias → (term key, alias as declared).
+    aliases: BTreeMap<String, (String, String)>,
+    /// `(winner, shadowed)` pairs for terms defined by more than one source.
+    collisions: Vec<(String, PathBuf)>,
+    /// The ontology directories, in precedence order.
+    sources: Vec<PathBuf>,
+}
+
+/// Markdown files under `dir`, recursively, sorted.
+fn markdown_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            markdown_files(&path, out);
+        } else if path.extension().is_some_and(|e| e == "md") {
+            out.push(path);
+        }
+    }
+}
+
+/// Lowercase term key for a file under `src`.
+fn term_key(src: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(src).ok()?.with_extension("");
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Resolve `..` and `.` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+impl TermIndex {
+    /// Read the term files under `src/` of each ontology directory, earlier
+    /// directories taking precedence. Directories that don't exist are
+    /// skipped.
+    pub fn load(sources: &[PathBuf]) -> Self {
+        let mut index = Self {
+            sources: sources.to_vec(),
+            ..Self::default()
+        };
+        for source in sources {
+            let src = source.join("src");
+            let mut files = Vec::new();
+            markdown_files(&src, &mut files);
+            for path in files {
+                if path.file_name().is_some_and(|n| n == SUMMARY) {
+                    continue;
+                }
+                let Some(key) = term_key(&src, &path) else {
+                    continue;
+                };
+                if let Some(existing) = index.terms.get(&key) {
+                    index.collisions.push((existing.name.clone(), path));
+                    continue;
+                }
+                index.insert(key, source, path);
+            }
+        }
+        index
+    }
+
+    fn insert(&mut self, key: String, source: &Path, path: PathBuf) {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = strip_front_matter(&content)
+            .lines()
+            .find_map(|l| l.strip_prefix("# "))
+            .map(|t| t.trim().replace(' ', "-"));
+        let leaf = title
+            .filter(|t| t.to_lowercase() == stem.to_lowercase())
+            .unwrap_or(stem);
+        let name = match path
+            .parent()
+            .and_then(|p| p.strip_prefix(source.join("src")).ok())
+        {
+            Some(ns) if !ns.as_os_str().is_empty() => {
+                format!("{}/{}", ns.to_string_lossy().replace('\\', "/"), leaf)
+            }
+            _ => leaf,
+        };
+        let fm = parse_front_matter(&content).unwrap_or_default();
+        let aliases = fm.list("aliases");
+        // Annotations can't contain spaces: `context window` is written
+        // `[term:context-window]`.
+        for alias in &aliases {
+            let spelling = alias.replace(' ', "-");
+            self.aliases
+                .entry(spelling.to_lowercase())
+                .or_insert_with(|| (key.clone(), spelling));
+        }
+        self.terms.insert(
+            key,
+            Term {
+                name,
+                path,
+                source: source.to_path_buf(),
+                aliases,
+                is_a: fm.list("is_a"),
+            },
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = &Term> {
+        self.terms.values()
+    }
+
+    /// The term `name` refers to, directly or through an alias, and the
+    /// spelling it was declared with.
+    pub fn lookup(&self, name: &str) -> Option<(&Term, &str)> {
+        let key = name.to_lowercase();
+        if let Some(term) = self.terms.get(&key) {
+            return Some((term, term.name.as_str()));
+        }
+        let (term_key, spelling) = self.aliases.get(&key)?;
+        Some((&self.terms[term_key], spelling.as_str()))
+    }
+
+    /// Closest term name or alias to `name`, if any is similar enough.
+    pub fn suggest(&self, name: &str) -> Option<&str> {
+        let lower = name.to_lowercase();
+        self.terms
+            .values()
+            .map(|t| t.name.as_str())
+            .chain(self.aliases.values().map(|(_, a)| a.as_str()))
+            .map(|c| {
+                (
+                    strsim::normalized_damerau_levenshtein(&lower, &c.to_lowercase()),
+                    c,
+                )
+            })
+            .filter(|(score, _)| *score >= 0.7)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, c)| c)
+    }
+
+    /// The ontology directories the index was loaded from.
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// The first ontology directory that exists, used to resolve and
+    /// display term paths.
+    fn primary(&self) -> Option<&Path> {
+        self.sources
+            .iter()
+            .find(|s| s.exists())
+            .map(PathBuf::as_path)
+    }
+
+    /// Term files shadowed by a source with higher precedence: the winning
+    /// term's name and the ignored file.
+    pub fn collisions(&self) -> &[(String, PathBuf)] {
+        &self.collisions
+    }
+}
 
 /// Scan `content` for `[term:Name]` annotations and verify each term
-/// has a `.md` file under `ontology_dir/src/`.
+/// has a `.md` file under `src/` of one of the index's sources or is
+/// declared as an alias.
 ///
-/// Returns an empty vec if `ontology_dir` does not exist (ontology not configured).
-pub fn check_ontology_terms(file: &str, content: &str, ontology_dir: &Path) -> Vec<Issue> {
-    if !ontology_dir.exists() {
+/// Unknown terms get a "did you mean" suggestion from the term names and
+/// aliases. With [`TermCase::Strict`], annotations whose case differs from
+/// the canonical spelling are warnings.
+///
+/// Returns an empty vec if none of the sources exists (ontology not
+/// configured).
+pub fn check_ontology_terms(
+    file: &str,
+    content: &str,
+    index: &TermIndex,
+    case: TermCase,
+) -> Vec<Issue> {
+    let Some(primary) = index.primary() else {
         return Vec::new();
+    };
+    term_ref_issues(file, content, index, primary, case)
+}
+
+/// Ontology directories for the project at `root`.
+///
+/// The first non-empty of: `explicit` (`--ontology-dir`), `configured`
+/// (`[ontology] dirs`, relative to `root`), or the directory holding an
+/// [`EXISTENCE_MANIFEST`] at the root or one level below it, followed by
+/// its `[sources]` that `existence fetch` has cloned into
+/// `~/.existence/sources/`.
+pub fn resolve_ontology_dirs(
+    root: &Path,
+    explicit: &[PathBuf],
+    configured: &[PathBuf],
+) -> Vec<PathBuf> {
+    if !explicit.is_empty() {
+        return explicit.to_vec();
     }
+    if !configured.is_empty() {
+        return configured.iter().map(|d| root.join(d)).collect();
+    }
+    let Some(dir) = find_existence_dir(root) else {
+        return Vec::new();
+    };
+    let home = existence::config::home_dir().ok();
+    let mut dirs = vec![dir.clone()];
+    dirs.extend(fetched_sources(&dir, home.as_deref()));
+    dirs
+}
+
+/// The root or first top-level directory (by name) with an
+/// [`EXISTENCE_MANIFEST`].
+fn find_existence_dir(root: &Path) -> Option<PathBuf> {
+    if root.join(EXISTENCE_MANIFEST).is_file() {
+        return Some(root.to_path_buf());
+    }
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_dir()
+                && !p
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    dirs.sort();
+    dirs.into_iter()
+        .find(|d| d.join(EXISTENCE_MANIFEST).is_file())
+}
 
-    let re = Regex::new(r"\[term:([A-Za-z][A-Za-z0-9_-]*)\]").expect("valid regex");
+/// `github:org/repo` sources of the manifest in `dir` that have been fetched
+/// under `home`.
+fn fetched_sources(dir: &Path, home: Option<&Path>) -> Vec<PathBuf> {
+    let (Some(home), Ok(config)) = (
+        home,
+        existence::config::Config::load(&dir.join(EXISTENCE_MANIFEST)),
+    ) else {
+        return Vec::new();
+    };
+    config
+        .sources
+        .values()
+        .filter_map(|spec| spec.strip_prefix("github:"))
+        .map(|repo| home.join(".existence/sources").join(repo))
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+/// Warn once per file, at the first `[term:Name]` annotation, when no
+/// ontology is configured to check annotations against. Annotations in
+/// code are examples, not uses, and don't count.
+pub fn check_unconfigured_terms(file: &str, content: &str) -> Vec<Issue> {
+    let mut first = None;
+    let mut count = 0;
+    for (n, line) in prose_lines(content) {
+        let found = TERM_RE.find_iter(&mask_code_spans(line)).count();
+        if found > 0 {
+            first.get_or_insert(n);
+            count += found;
+        }
+    }
+    let Some(first) = first else {
+        return Vec::new();
+    };
+    vec![Issue {
+        file: file.to_string(),
+        line: first,
+        end_line: 0,
+        message: format!(
+            "{} [term:...] annotation(s) but no ontology is configured \u{2014} set `[ontology] dirs` in instruction-files.toml, add an {}, or pass --ontology-dir",
+            count, EXISTENCE_MANIFEST
+        ),
+        warning: true,
+    }]
+}
+
+/// `[term:Name]` annotations in `content` that don't resolve in `index`.
+fn term_ref_issues(
+    file: &str,
+    content: &str,
+    index: &TermIndex,
+    primary: &Path,
+    case: TermCase,
+) -> Vec<Issue> {
     let mut issues = Vec::new();
 
     for (line_num, line) in content.lines().enumerate() {
-        for cap in re.captures_iter(line) {
+        for cap in TERM_RE.captures_iter(line) {
             let term = &cap[1];
-            let term_lower = term.to_lowercase();
-            // Check for term.md in ontology_dir/src/
-            let term_path = ontology_dir.join("src").join(format!("{}.md", term_lower));
-            if !term_path.exists() {
-                issues.push(Issue {
-                    file: file.to_string(),
-                    line: line_num + 1,
-                    end_line: 0,
-                    message: format!(
+            let (message, warning) = match index.lookup(term) {
+                Some((_, spelling)) if case == TermCase::Strict && spelling != term => (
+                    format!(
+                        "Ontology term '{}' doesn't match canonical spelling '{}'",
+                        term, spelling
+                    ),
+                    true,
+                ),
+                Some(_) => continue,
+                None => {
+                    let term_path = primary
+                        .join("src")
+                        .join(format!("{}.md", term.to_lowercase()));
+                    let mut message = format!(
                         "Ontology term '{}' not found (expected {})",
                         term,
                         term_path.display()
-                    ),
+                    );
+                    if let Some(suggestion) = index.suggest(term) {
+                        message.push_str(&format!(" \u{2014} did you mean '{}'?", suggestion));
+                    }
+                    (message, false)
+                }
+            };
+            issues.push(Issue {
+                file: file.to_string(),
+                line: line_num + 1,
+                end_line: 0,
+                message,
+                warning,
+            });
+        }
+    }
+
+    issues
+}
+
+/// `[term:Name]` annotations in prose, outside code blocks and code spans,
+/// as `(line, name)`.
+pub(crate) fn prose_annotations(content: &str) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    for (n, line) in prose_lines(content) {
+        for cap in TERM_RE.captures_iter(&mask_code_spans(line)) {
+            found.push((n, cap[1].to_string()));
+        }
+    }
+    found
+}
+
+pub(crate) fn display_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Cross-reference every term file in `index` against the `[term:Name]`
+/// annotations in `files`. Annotations through an alias count toward the
+/// aliased term; annotations in code blocks and code spans don't count.
+///
+/// Returns warnings for terms no instruction file annotates (worded as
+/// orphaned when no other term file links to them either) and for term
+/// files shadowed by a higher-precedence source, plus the usage count of
+/// every term, sorted by name.
+pub fn check_ontology_coverage(
+    files: &[PathBuf],
+    root: &Path,
+    index: &TermIndex,
+) -> (Vec<Issue>, Vec<(String, usize)>) {
+    let mut usage: BTreeMap<String, usize> =
+        index.terms().map(|t| (t.name.to_lowercase(), 0)).collect();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        for (_, name) in prose_annotations(&content) {
+            if let Some((term, _)) = index.lookup(&name) {
+                *usage.entry(term.name.to_lowercase()).or_default() += 1;
+            }
+        }
+    }
+
+    // Links between term files (and from SUMMARY.md), as normalized paths.
+    let mut linked = HashSet::new();
+    let summaries = index.sources().iter().map(|s| s.join("src").join(SUMMARY));
+    for path in index.terms().map(|t| t.path.clone()).chain(summaries) {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for cap in MD_LINK_RE.captures_iter(&content) {
+            let target = normalize(&dir.join(&cap[1]));
+            if target != normalize(&path) {
+                linked.insert(target);
+            }
+        }
+    }
+
+    let mut issues = Vec::new();
+    for term in index.terms() {
+        if usage[&term.name.to_lowercase()] > 0 {
+            continue;
+        }
+        let message = if linked.contains(&normalize(&term.path)) {
+            format!(
+                "Ontology term '{}' is not referenced by any instruction file",
+                term.name
+            )
+        } else {
+            format!(
+                "Ontology term '{}' is orphaned: not referenced by any instruction file or linked from another term",
+                term.name
+            )
+        };
+        issues.push(Issue {
+            file: display_path(&term.path, root),
+            line: 0,
+            end_line: 0,
+            message,
+            warning: true,
+        });
+    }
+    for (name, shadowed) in index.collisions() {
+        let (winner, _) = index.lookup(name).expect("collision winner is indexed");
+        issues.push(Issue {
+            file: display_path(shadowed, root),
+            line: 0,
+            end_line: 0,
+            message: format!(
+                "Ontology term '{}' is also defined in {}, which takes precedence",
+                name,
+                display_path(&winner.path, root)
+            ),
+            warning: true,
+        });
+    }
+
+    (issues, usage.into_iter().collect())
+}
+
+/// Lowercase with spaces and underscores as `-`, for comparing titles with
+/// file names.
+fn slug(s: &str) -> String {
+    s.trim().to_lowercase().replace([' ', '_'], "-")
+}
+
+/// A line of running text, as opposed to a heading, list item, quote,
+/// table row or HTML comment.
+fn is_paragraph(line: &str) -> bool {
+    let t = line.trim();
+    !t.is_empty()
+        && !t.starts_with(['#', '-', '*', '+', '>', '|'])
+        && !t.starts_with("<!--")
+        && !t
+            .split_once(". ")
+            .is_some_and(|(n, _)| n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Prose lines of a term file after its front matter.
+fn body_lines(content: &str) -> Vec<(usize, &str)> {
+    let body_start = parse_front_matter(content).map_or(0, |fm| fm.end_line);
+    prose_lines(content)
+        .into_iter()
+        .filter(|(n, _)| *n > body_start)
+        .collect()
+}
+
+/// Lines under a `## Related` heading, up to the next heading.
+fn related_lines<'a>(lines: &[(usize, &'a str)]) -> Vec<(usize, &'a str)> {
+    let mut in_related = false;
+    let mut out = Vec::new();
+    for &(n, line) in lines {
+        if line.starts_with('#') {
+            in_related = line
+                .strip_prefix("## ")
+                .is_some_and(|h| h.trim().eq_ignore_ascii_case("related"));
+        } else if in_related {
+            out.push((n, line));
+        }
+    }
+    out
+}
+
+/// The first definition paragraph of a term file, joined into one line.
+pub(crate) fn definition(content: &str) -> Option<String> {
+    let mut paragraph = Vec::new();
+    for (_, line) in body_lines(content) {
+        if is_paragraph(line) {
+            paragraph.push(line.trim());
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+    (!paragraph.is_empty()).then(|| paragraph.join(" "))
+}
+
+/// Terms listed under `## Related` in `term`'s file, as links to term files
+/// or `[term:Name]` references.
+pub(crate) fn related_terms<'a>(term: &Term, content: &str, index: &'a TermIndex) -> Vec<&'a Term> {
+    let dir = term.path.parent().unwrap_or(Path::new(""));
+    let mut related: Vec<&Term> = Vec::new();
+    for (_, line) in related_lines(&body_lines(content)) {
+        let linked = ANY_LINK_RE.captures_iter(line).filter_map(|cap| {
+            let path = cap[1].split('#').next().unwrap_or("");
+            let resolved = normalize(&dir.join(path));
+            index.terms().find(|t| normalize(&t.path) == resolved)
+        });
+        let referenced = TERM_RE
+            .captures_iter(line)
+            .filter_map(|cap| index.lookup(&cap[1]).map(|(t, _)| t));
+        for other in linked.chain(referenced) {
+            if other.path != term.path && !related.iter().any(|r| r.path == other.path) {
+                related.push(other);
+            }
+        }
+    }
+    related
+}
+
+/// Validate the term files in `index`: each needs an H1 matching the term
+/// name and a definition paragraph; links under `## Related` must point at
+/// other terms; `[term:Name]` references must resolve; and `is_a` front
+/// matter must name existing terms without forming a cycle.
+///
+/// All findings are errors. Returns an empty vec if none of the sources
+/// exists.
+pub fn check_term_files(root: &Path, index: &TermIndex) -> Vec<Issue> {
+    let Some(primary) = index.primary() else {
+        return Vec::new();
+    };
+    let term_paths: HashSet<PathBuf> = index.terms().map(|t| normalize(&t.path)).collect();
+    let mut issues = Vec::new();
+
+    for term in index.terms() {
+        let Ok(content) = std::fs::read_to_string(&term.path) else {
+            continue;
+        };
+        let rel = display_path(&term.path, root);
+        let mut push = |line: usize, message: String| {
+            issues.push(Issue {
+                file: rel.clone(),
+                line,
+                end_line: 0,
+                message,
+                warning: false,
+            });
+        };
+        let leaf = term.name.rsplit('/').next().unwrap_or(&term.name);
+        let stem = term
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let body_start = parse_front_matter(&content).map_or(0, |fm| fm.end_line);
+        let lines = body_lines(&content);
+
+        let title = lines
+            .iter()
+            .find_map(|(n, l)| l.strip_prefix("# ").map(|t| (*n, t.trim())));
+        match title {
+            None => push(
+                body_start + 1,
+                format!("Term file for '{}' has no `# Title`", term.name),
+            ),
+            Some((n, t)) if slug(t) != slug(&stem) => push(
+                n,
+                format!("Title '{}' doesn't match term name '{}'", t, leaf),
+            ),
+            Some(_) => {}
+        }
+        if !lines.iter().any(|(_, l)| is_paragraph(l)) {
+            push(
+                title.map_or(body_start + 1, |(n, _)| n),
+                format!("Term '{}' has no definition paragraph", term.name),
+            );
+        }
+
+        let dir = term.path.parent().unwrap_or(Path::new(""));
+        for (n, line) in related_lines(&lines) {
+            for cap in ANY_LINK_RE.captures_iter(line) {
+                let target = &cap[1];
+                if target.starts_with('#') {
+                    continue;
+                }
+                let path = target.split('#').next().unwrap_or(target);
+                let resolved = normalize(&dir.join(path));
+                if target.contains("://") || !term_paths.contains(&resolved) {
+                    push(
+                        n,
+                        format!("Related link `{}` is not an existing term", target),
+                    );
+                } else if resolved == normalize(&term.path) {
+                    push(
+                        n,
+                        format!("Related link `{}` points at the term itself", target),
+                    );
+                }
+            }
+        }
+
+        issues.extend(term_ref_issues(
+            &rel,
+            &content,
+            index,
+            primary,
+            TermCase::Insensitive,
+        ));
+    }
+
+    issues.extend(is_a_issues(root, index));
+    issues
+}
+
+/// Unknown `is_a` parents and cycles in the `is_a` graph, each cycle
+/// reported once at its alphabetically first term.
+fn is_a_issues(root: &Path, index: &TermIndex) -> Vec<Issue> {
+    let field_line = |term: &Term| {
+        std::fs::read_to_string(&term.path)
+            .ok()
+            .and_then(|c| parse_front_matter(&c))
+            .and_then(|fm| fm.field("is_a").map(|f| f.line))
+            .unwrap_or(1)
+    };
+    let mut issues = Vec::new();
+    let mut edges: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for term in index.terms() {
+        let key = term.name.to_lowercase();
+        for parent in &term.is_a {
+            match index.lookup(parent) {
+                Some((p, _)) => edges
+                    .entry(key.clone())
+                    .or_default()
+                    .push(p.name.to_lowercase()),
+                None => issues.push(Issue {
+                    file: display_path(&term.path, root),
+                    line: field_line(term),
+                    end_line: 0,
+                    message: format!("`is_a` parent '{}' is not an ontology term", parent),
                     warning: false,
-                });
+                }),
+            }
+        }
+    }
+
+    fn visit(
+        key: &str,
+        edges: &BTreeMap<String, Vec<String>>,
+        stack: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        if let Some(pos) = stack.iter().position(|k| k == key) {
+            let mut cycle = stack[pos..].to_vec();
+            let min = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+            cycle.rotate_left(min);
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
             }
+            return;
+        }
+        if done.contains(key) {
+            return;
         }
+        stack.push(key.to_string());
+        for next in edges.get(key).into_iter().flatten() {
+            visit(next, edges, stack, done, cycles);
+        }
+        stack.pop();
+        done.insert(key.to_string());
     }
 
+    let mut cycles = Vec::new();
+    let mut done = HashSet::new();
+    for key in edges.keys() {
+        visit(key, &edges, &mut Vec::new(), &mut done, &mut cycles);
+    }
+    for cycle in cycles {
+        let names: Vec<&str> = cycle
+            .iter()
+            .chain(cycle.first())
+            .filter_map(|k| index.lookup(k).map(|(t, _)| t.name.as_str()))
+            .collect();
+        let (first, _) = index.lookup(&cycle[0]).expect("cycle terms are indexed");
+        issues.push(Issue {
+            file: display_path(&first.path, root),
+            line: field_line(first),
+            end_line: 0,
+            message: format!("`is_a` cycle: {}", names.join(" \u{2192} ")),
+            warning: false,
+        });
+    }
     issues
 }
 
+/// A plain-prose mention of a term that could be annotated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermMention {
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte range of the mention within the line.
+    pub start: usize,
+    pub end: usize,
+    /// The annotation to write in its place (`[term:Name]`).
+    pub annotation: String,
+}
+
+/// `line` with code spans and [`NO_MENTION_RE`] matches blanked out,
+/// byte offsets preserved.
+fn mask_mentions(line: &str) -> String {
+    let masked = mask_code_spans(line);
+    let mut out = String::with_capacity(line.len());
+    for (orig, m) in line.chars().zip(masked.chars()) {
+        if m == ' ' && orig != ' ' {
+            out.push_str(&" ".repeat(orig.len_utf8()));
+        } else {
+            out.push(orig);
+        }
+    }
+    let mut bytes = out.into_bytes();
+    for m in NO_MENTION_RE.find_iter(line) {
+        bytes[m.range()].fill(b' ');
+    }
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+/// First unannotated mention of each term per section (headings start
+/// sections). Matching is case-sensitive on the canonical spelling and the
+/// declared aliases, at word boundaries, outside front matter, code, links,
+/// headings and the generated glossary; `-` in a name also matches a space. Sections that already
+/// annotate a term don't get a suggestion for it.
+pub fn find_term_mentions(content: &str, index: &TermIndex) -> Vec<TermMention> {
+    // Spelling → (term key, annotation name), with spaces written as `-`.
+    // Leaf names shared by several terms are ambiguous and skipped.
+    let mut spellings: BTreeMap<String, Option<(String, String)>> = BTreeMap::new();
+    for term in index.terms() {
+        let key = term.name.to_lowercase();
+        let leaf = term.name.rsplit('/').next().unwrap_or(&term.name);
+        let ns = &term.name[..term.name.len() - leaf.len()];
+        spellings
+            .entry(leaf.replace(' ', "-"))
+            .and_modify(|e| *e = None)
+            .or_insert_with(|| Some((key.clone(), term.name.clone())));
+        for alias in &term.aliases {
+            let alias = alias.replace(' ', "-");
+            spellings
+                .entry(alias.clone())
+                .or_insert_with(|| Some((key.clone(), format!("{}{}", ns, alias))));
+        }
+    }
+    let spellings: BTreeMap<String, (String, String)> = spellings
+        .into_iter()
+        .filter_map(|(k, v)| Some((k, v?)))
+        .collect();
+    if spellings.is_empty() {
+        return Vec::new();
+    }
+    let mut alternatives: Vec<&String> = spellings.keys().collect();
+    alternatives.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let pattern = alternatives
+        .iter()
+        .map(|s| {
+            s.split('-')
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join("[- ]")
+        })
+        .collect::<Vec<_>>()
+        .join("|");
+    let Ok(re) = Regex::new(&format!(r"\b(?:{})\b", pattern)) else {
+        return Vec::new();
+    };
+
+    let heading_lines: HashSet<usize> = headings(content).iter().map(|(n, _, _)| *n).collect();
+    let glossary = glossary_lines(content);
+    let lines: Vec<(usize, &str)> = body_lines(content)
+        .into_iter()
+        .filter(|(n, _)| !glossary.is_some_and(|(start, end)| (start..=end).contains(n)))
+        .collect();
+    let mut sections: Vec<&[(usize, &str)]> = Vec::new();
+    let mut start = 0;
+    for (i, (n, _)) in lines.iter().enumerate() {
+        if heading_lines.contains(n) {
+            sections.push(&lines[start..i]);
+            start = i;
+        }
+    }
+    sections.push(&lines[start..]);
+
+    let mut mentions = Vec::new();
+    for section in sections {
+        let mut done: HashSet<String> = section
+            .iter()
+            .flat_map(|(_, l)| TERM_RE.captures_iter(l))
+            .filter_map(|c| index.lookup(&c[1]).map(|(t, _)| t.name.to_lowercase()))
+            .collect();
+        for (n, line) in section {
+            if heading_lines.contains(n) {
+                continue;
+            }
+            for m in re.find_iter(&mask_mentions(line)) {
+                let spelled = m.as_str().replace(' ', "-");
+                let Some((key, annotation)) = spellings.get(&spelled) else {
+                    continue;
+                };
+                if done.insert(key.clone()) {
+                    mentions.push(TermMention {
+                        line: *n,
+                        start: m.start(),
+                        end: m.end(),
+                        annotation: format!("[term:{}]", annotation),
+                    });
+                }
+            }
+        }
+    }
+    mentions
+}
+
+/// Suggest annotating the first plain mention of each term per section.
+///
+/// Returns warnings; empty if the index has no terms.
+pub fn check_term_mentions(file: &str, content: &str, index: &TermIndex) -> Vec<Issue> {
+    let lines: Vec<&str> = content.lines().collect();
+    find_term_mentions(content, index)
+        .into_iter()
+        .map(|m| Issue {
+            file: file.to_string(),
+            line: m.line,
+            end_line: 0,
+            message: format!(
+                "Unannotated mention of ontology term '{}' \u{2014} write `{}`",
+                &lines[m.line - 1][m.start..m.end],
+                m.annotation
+            ),
+            warning: true,
+        })
+        .collect()
+}
+
+/// Replace up to `limit` suggested mentions (in file order) with their
+/// annotations. Returns the new content and the number inserted.
+pub fn annotate_terms(content: &str, index: &TermIndex, limit: usize) -> (String, usize) {
+    let mentions: Vec<TermMention> = find_term_mentions(content, index)
+        .into_iter()
+        .take(limit)
+        .collect();
+    if mentions.is_empty() {
+        return (content.to_string(), 0);
+    }
+    let mut out = String::with_capacity(content.len());
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let mut pos = 0;
+        for m in mentions.iter().filter(|m| m.line == i + 1) {
+            out.push_str(&line[pos..m.start]);
+            out.push_str(&m.annotation);
+            pos = m.end;
+        }
+        out.push_str(&line[pos..]);
+    }
+    (out, mentions.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,8 +961,17 @@ mod tests {
         fs::write(onto_dir.join("src/existence.md"), "# Existence\n").unwrap();
 
         let content = "See [term:Existence] for details.\n";
-        let issues = check_ontology_terms("CLAUDE.md", content, &onto_dir);
-        assert!(issues.is_empty(), "expected no issues, got {} issue(s)", issues.len());
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&[onto_dir]),
+            TermCase::Insensitive,
+        );
+        assert!(
+            issues.is_empty(),
+            "expected no issues, got {} issue(s)",
+            issues.len()
+        );
     }
 
     #[test]
@@ -70,7 +981,12 @@ mod tests {
         fs::create_dir_all(onto_dir.join("src")).unwrap();
 
         let content = "See [term:Nonexistent] here.\n";
-        let issues = check_ontology_terms("CLAUDE.md", content, &onto_dir);
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&[onto_dir]),
+            TermCase::Insensitive,
+        );
         assert_eq!(issues.len(), 1);
         assert!(issues[0].message.contains("Nonexistent"));
         assert!(!issues[0].warning);
@@ -83,7 +999,12 @@ mod tests {
         let missing = tmp.path().join("does-not-exist");
 
         let content = "See [term:Anything] here.\n";
-        let issues = check_ontology_terms("CLAUDE.md", content, &missing);
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&[missing]),
+            TermCase::Insensitive,
+        );
         assert!(issues.is_empty());
     }
 
@@ -95,7 +1016,12 @@ mod tests {
         fs::write(onto_dir.join("src/scope.md"), "# Scope\n").unwrap();
 
         let content = "Both [term:Scope] and [term:Missing] appear.\n";
-        let issues = check_ontology_terms("AGENTS.md", content, &onto_dir);
+        let issues = check_ontology_terms(
+            "AGENTS.md",
+            content,
+            &TermIndex::load(&[onto_dir]),
+            TermCase::Insensitive,
+        );
         assert_eq!(issues.len(), 1);
         assert!(issues[0].message.contains("Missing"));
     }
@@ -108,7 +1034,467 @@ mod tests {
         fs::write(onto_dir.join("src/context.md"), "# Context\n").unwrap();
 
         let content = "See [term:Context] here.\n";
-        let issues = check_ontology_terms("CLAUDE.md", content, &onto_dir);
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&[onto_dir]),
+            TermCase::Insensitive,
+        );
         assert!(issues.is_empty());
     }
+
+    #[test]
+    fn coverage_reports_unused_and_orphaned_terms() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let src = root.join("ontology/src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("SUMMARY.md"), "- [Scope](./scope.md)\n").unwrap();
+        fs::write(
+            src.join("context.md"),
+            "# Context\n\nA [scope](./scope.md).\n",
+        )
+        .unwrap();
+        fs::write(src.join("scope.md"), "# Scope\n").unwrap();
+        fs::write(src.join("entity.md"), "# Entity\n").unwrap();
+        fs::write(
+            root.join("CLAUDE.md"),
+            "[term:Context] and [term:context].\nNot `[term:Scope]`.\n\n```md\n[term:Entity]\n```\n",
+        )
+        .unwrap();
+
+        let files = vec![root.join("CLAUDE.md")];
+        let (issues, usage) =
+            check_ontology_coverage(&files, root, &TermIndex::load(&[root.join("ontology")]));
+        assert_eq!(
+            usage,
+            vec![
+                ("context".to_string(), 2),
+                ("entity".to_string(), 0),
+                ("scope".to_string(), 0),
+            ]
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|i| i.warning));
+        assert_eq!(issues[0].file, "ontology/src/entity.md");
+        assert!(issues[0].message.contains("'Entity' is orphaned"));
+        assert_eq!(
+            issues[1].message,
+            "Ontology term 'Scope' is not referenced by any instruction file"
+        );
+    }
+
+    fn alias_ontology(tmp: &TempDir) -> PathBuf {
+        let onto_dir = tmp.path().join("ontology");
+        fs::create_dir_all(onto_dir.join("src")).unwrap();
+        fs::write(
+            onto_dir.join("src/context.md"),
+            "---\naliases: [ctx, context-window]\n---\n# Context\n",
+        )
+        .unwrap();
+        fs::write(onto_dir.join("src/scope.md"), "# Scope\n").unwrap();
+        onto_dir
+    }
+
+    #[test]
+    fn aliases_resolve_and_count_toward_term() {
+        let tmp = TempDir::new().unwrap();
+        let sources = [alias_ontology(&tmp)];
+        let content = "Watch the [term:context-window] and [term:CTX].\n";
+        assert!(
+            check_ontology_terms(
+                "CLAUDE.md",
+                content,
+                &TermIndex::load(&sources),
+                TermCase::Insensitive
+            )
+            .is_empty()
+        );
+
+        let doc = tmp.path().join("CLAUDE.md");
+        fs::write(&doc, content).unwrap();
+        let (_, usage) = check_ontology_coverage(&[doc], tmp.path(), &TermIndex::load(&sources));
+        assert_eq!(usage[0], ("context".to_string(), 2));
+    }
+
+    #[test]
+    fn unknown_term_suggests_closest_name_or_alias() {
+        let tmp = TempDir::new().unwrap();
+        let sources = [alias_ontology(&tmp)];
+        let content = "[term:Contxt] [term:context-windw] [term:Zebra]\n";
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&sources),
+            TermCase::Insensitive,
+        );
+        assert_eq!(issues.len(), 3);
+        assert!(
+            issues[0]
+                .message
+                .ends_with("\u{2014} did you mean 'Context'?")
+        );
+        assert!(
+            issues[1]
+                .message
+                .ends_with("\u{2014} did you mean 'context-window'?")
+        );
+        assert!(!issues[2].message.contains("did you mean"));
+    }
+
+    #[test]
+    fn strict_case_flags_mismatched_spelling() {
+        let tmp = TempDir::new().unwrap();
+        let sources = [alias_ontology(&tmp)];
+        let content = "[term:context] [term:Context] [term:CTX] [term:ctx]\n";
+        assert!(
+            check_ontology_terms(
+                "CLAUDE.md",
+                content,
+                &TermIndex::load(&sources),
+                TermCase::Insensitive
+            )
+            .is_empty()
+        );
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&sources),
+            TermCase::Strict,
+        );
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Ontology term 'context' doesn't match canonical spelling 'Context'",
+                "Ontology term 'CTX' doesn't match canonical spelling 'ctx'",
+            ]
+        );
+        assert!(issues.iter().all(|i| i.warning));
+    }
+
+    #[test]
+    fn namespaced_terms_resolve_to_nested_files() {
+        let tmp = TempDir::new().unwrap();
+        let onto_dir = tmp.path().join("ontology");
+        fs::create_dir_all(onto_dir.join("src/billing")).unwrap();
+        fs::write(onto_dir.join("src/billing/invoice.md"), "# Invoice\n").unwrap();
+        fs::write(onto_dir.join("src/invoice.md"), "# Invoice\n").unwrap();
+
+        let content = "[term:billing/Invoice] [term:Invoice] [term:billing/Invoce]\n";
+        let sources = [onto_dir.clone()];
+        let issues = check_ontology_terms(
+            "CLAUDE.md",
+            content,
+            &TermIndex::load(&sources),
+            TermCase::Strict,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0]
+                .message
+                .starts_with("Ontology term 'billing/Invoce' not found")
+        );
+        assert!(
+            issues[0]
+                .message
+                .ends_with("did you mean 'billing/Invoice'?")
+        );
+
+        let doc = tmp.path().join("CLAUDE.md");
+        fs::write(&doc, content).unwrap();
+        let (_, usage) = check_ontology_coverage(&[doc], tmp.path(), &TermIndex::load(&sources));
+        assert_eq!(
+            usage,
+            vec![
+                ("billing/invoice".to_string(), 1),
+                ("invoice".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn earlier_sources_take_precedence_and_collisions_are_reported() {
+        let tmp = TempDir::new().unwrap();
+        let local = tmp.path().join("ontology");
+        let shared = tmp.path().join("shared/ontology");
+        fs::create_dir_all(local.join("src")).unwrap();
+        fs::create_dir_all(shared.join("src")).unwrap();
+        fs::write(local.join("src/scope.md"), "# Scope\n").unwrap();
+        fs::write(shared.join("src/scope.md"), "# scope\n").unwrap();
+        fs::write(shared.join("src/entity.md"), "# Entity\n").unwrap();
+
+        let sources = [local.clone(), shared.clone()];
+        let content = "[term:Scope] [term:Entity]\n";
+        assert!(
+            check_ontology_terms(
+                "CLAUDE.md",
+                content,
+                &TermIndex::load(&sources),
+                TermCase::Strict
+            )
+            .is_empty()
+        );
+
+        let doc = tmp.path().join("CLAUDE.md");
+        fs::write(&doc, content).unwrap();
+        let (issues, usage) =
+            check_ontology_coverage(&[doc], tmp.path(), &TermIndex::load(&sources));
+        assert_eq!(usage.len(), 2);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file, "shared/ontology/src/scope.md");
+        assert_eq!(
+            issues[0].message,
+            "Ontology term 'Scope' is also defined in ontology/src/scope.md, which takes precedence"
+        );
+    }
+
+    #[test]
+    fn term_file_structure() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let src = root.join("ontology/src");
+        fs::create_dir_all(src.join("billing")).unwrap();
+        fs::write(
+            src.join("scope.md"),
+            "# Scope\n\nThe boundary of a [term:Context].\n\n## Related\n\n- [Entity](./entity.md)\n- [Billing](./billing/invoice.md)\n",
+        )
+        .unwrap();
+        fs::write(src.join("context.md"), "# Context\n\n- just a list\n").unwrap();
+        fs::write(src.join("entity.md"), "Something that exists.\n").unwrap();
+        fs::write(
+            src.join("billing/invoice.md"),
+            "# Bill\n\nA request for payment. See [term:Entty].\n\n## Related\n\n- [Scope](../scope.md)\n- [Payment](./payment.md)\n- [Docs](https://example.com)\n",
+        )
+        .unwrap();
+
+        let issues = check_term_files(root, &TermIndex::load(&[root.join("ontology")]));
+        let found: Vec<(&str, usize, &str)> = issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.line, i.message.as_str()))
+            .collect();
+        let missing = format!(
+            "Ontology term 'Entty' not found (expected {}) \u{2014} did you mean 'entity'?",
+            root.join("ontology/src/entty.md").display()
+        );
+        let invoice = "ontology/src/billing/invoice.md";
+        assert_eq!(
+            found,
+            vec![
+                (invoice, 1, "Title 'Bill' doesn't match term name 'invoice'"),
+                (
+                    invoice,
+                    8,
+                    "Related link `./payment.md` is not an existing term"
+                ),
+                (
+                    invoice,
+                    9,
+                    "Related link `https://example.com` is not an existing term"
+                ),
+                (invoice, 3, missing.as_str()),
+                (
+                    "ontology/src/context.md",
+                    1,
+                    "Term 'Context' has no definition paragraph"
+                ),
+                (
+                    "ontology/src/entity.md",
+                    1,
+                    "Term file for 'entity' has no `# Title`"
+                ),
+            ]
+        );
+        assert!(issues.iter().all(|i| !i.warning));
+    }
+
+    #[test]
+    fn is_a_cycles_and_unknown_parents() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let src = root.join("ontology/src");
+        fs::create_dir_all(&src).unwrap();
+        let term = |name: &str, is_a: &str| {
+            fs::write(
+                src.join(format!("{}.md", name.to_lowercase())),
+                format!("---\nis_a: [{}]\n---\n# {}\n\nA definition.\n", is_a, name),
+            )
+            .unwrap();
+        };
+        term("Cat", "Mammal");
+        term("Mammal", "Animal");
+        term("Animal", "Cat");
+        term("Dog", "Mammal, Pet");
+
+        let issues = check_term_files(root, &TermIndex::load(&[root.join("ontology")]));
+        let messages: Vec<(&str, usize, &str)> = issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "ontology/src/dog.md",
+                    2,
+                    "`is_a` parent 'Pet' is not an ontology term"
+                ),
+                (
+                    "ontology/src/animal.md",
+                    2,
+                    "`is_a` cycle: Animal \u{2192} Cat \u{2192} Mammal \u{2192} Animal"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn mentions_first_per_section() {
+        let tmp = TempDir::new().unwrap();
+        let sources = [alias_ontology(&tmp)];
+        let content = "\
+# Context handling
+
+Context is limited. Keep Context small; the ctx fills up.
+Avoid `Context` in code and [Context](./context.md) in links.
+
+## Scope
+
+Pick a Scope. The context window matters.
+
+## Annotated
+
+The [term:Scope] is set; Scope again.
+
+```text
+Scope
+```
+";
+        let issues = check_term_mentions("CLAUDE.md", content, &TermIndex::load(&sources));
+        let found: Vec<(usize, &str)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    3,
+                    "Unannotated mention of ontology term 'Context' \u{2014} write `[term:Context]`"
+                ),
+                (
+                    8,
+                    "Unannotated mention of ontology term 'Scope' \u{2014} write `[term:Scope]`"
+                ),
+                (
+                    8,
+                    "Unannotated mention of ontology term 'context window' \u{2014} write `[term:context-window]`"
+                ),
+            ]
+        );
+        assert!(issues.iter().all(|i| i.warning));
+    }
+
+    #[test]
+    fn aliases_with_spaces_are_matched() {
+        let tmp = TempDir::new().unwrap();
+        let onto_dir = tmp.path().join("ontology");
+        fs::create_dir_all(onto_dir.join("src")).unwrap();
+        fs::write(
+            onto_dir.join("src/context.md"),
+            "---\naliases: [context window]\n---\n# Context\n",
+        )
+        .unwrap();
+        let index = TermIndex::load(&[onto_dir]);
+        let (fixed, n) = annotate_terms("# A\n\nThe context window fills.\n", &index, 5);
+        assert_eq!(n, 1);
+        assert_eq!(fixed, "# A\n\nThe [term:context-window] fills.\n");
+        assert_eq!(
+            index
+                .lookup("context-window")
+                .map(|(t, s)| (t.name.as_str(), s)),
+            Some(("Context", "context-window"))
+        );
+    }
+
+    #[test]
+    fn annotate_terms_is_rate_limited() {
+        let tmp = TempDir::new().unwrap();
+        let sources = [alias_ontology(&tmp)];
+        let content = "# A\n\nContext here.\n\n# B\n\nScope and Context.\n";
+        let (fixed, n) = annotate_terms(content, &TermIndex::load(&sources), 2);
+        assert_eq!(n, 2);
+        assert_eq!(
+            fixed,
+            "# A\n\n[term:Context] here.\n\n# B\n\n[term:Scope] and Context.\n"
+        );
+        let (again, n) = annotate_terms(&fixed, &TermIndex::load(&sources), 2);
+        assert_eq!(n, 1);
+        assert!(again.ends_with("[term:Scope] and [term:Context].\n"));
+    }
+
+    #[test]
+    fn front_matter_is_not_annotated() {
+        let tmp = TempDir::new().unwrap();
+        let index = TermIndex::load(&[alias_ontology(&tmp)]);
+        let content =
+            "---\nname: ctx\ndescription: Manage Context and Scope.\n---\n# Ctx\n\nContext here.\n";
+        let (fixed, n) = annotate_terms(content, &index, 5);
+        assert_eq!(n, 1);
+        assert_eq!(
+            fixed,
+            "---\nname: ctx\ndescription: Manage Context and Scope.\n---\n# Ctx\n\n[term:Context] here.\n"
+        );
+    }
+
+    #[test]
+    fn ontology_dirs_resolve_in_order() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let explicit = [PathBuf::from("/elsewhere")];
+        let configured = [PathBuf::from("docs/ontology")];
+        assert_eq!(
+            resolve_ontology_dirs(root, &explicit, &configured),
+            explicit
+        );
+        assert_eq!(
+            resolve_ontology_dirs(root, &[], &configured),
+            vec![root.join("docs/ontology")]
+        );
+        assert!(resolve_ontology_dirs(root, &[], &[]).is_empty());
+
+        let onto = root.join("ontology");
+        fs::create_dir_all(onto.join("src")).unwrap();
+        fs::create_dir_all(root.join(".cache")).unwrap();
+        fs::write(root.join(".cache").join(EXISTENCE_MANIFEST), "").unwrap();
+        fs::write(
+            onto.join(EXISTENCE_MANIFEST),
+            "[meta]\nname = \"acme\"\ndescription = \"\"\n\n[sources]\nkernel = \"github:org/kernel\"\nmissing = \"github:org/missing\"\n",
+        )
+        .unwrap();
+        assert_eq!(find_existence_dir(root), Some(onto.clone()));
+
+        let home = root.join("home");
+        let fetched = home.join(".existence/sources/org/kernel");
+        fs::create_dir_all(&fetched).unwrap();
+        assert_eq!(fetched_sources(&onto, Some(&home)), vec![fetched]);
+    }
+
+    #[test]
+    fn annotations_without_ontology_warn_once() {
+        let content =
+            "# Rules\n\nPlain text.\nUse [term:Context] and [term:Scope].\n[term:Context] again.\n";
+        let issues = check_unconfigured_terms("AGENTS.md", content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 4);
+        assert!(issues[0].warning);
+        assert!(
+            issues[0]
+                .message
+                .starts_with("3 [term:...] annotation(s) but no ontology")
+        );
+        assert!(check_unconfigured_terms("AGENTS.md", "No terms.\n").is_empty());
+        let code = "Write `[term:Name]` annotations.\n\n```md\n[term:Context]\n```\n";
+        assert!(check_unconfigured_terms("AGENTS.md", code).is_empty());
+    }
 }
@@ -0,0 +1,469 @@
+//! Runbook checks (agent-runbooks convention).
+//!
+//! Runbooks live in `.agent/runbooks/` (scaffolded by
+//! [`init_runbooks`](crate::runbooks::init_runbooks)) and in skills'
+//! `runbooks/` directories. Each needs a `# Title`, steps (a `## Steps` or
+//! `## Checklist` section, or a numbered list) and every section listed in
+//! `[runbooks] required_sections`; front matter, when present, is validated.
+//! Across files, runbooks mentioned by name ("see runbook `precommit`") or
+//! path must exist, and runbooks no other instruction file references are
+//! reported.
+
+use crate::config::RunbookConfig;
+use crate::markdown::{headings, parse_front_matter, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Project runbook directory, relative to the root.
+pub const RUNBOOK_DIR: &str = ".agent/runbooks";
+
+/// Front matter keys a runbook may declare.
+const FRONT_MATTER_KEYS: &[&str] = &["name", "description", "triggers", "tags"];
+/// Section headings that hold a runbook's steps.
+const STEPS_SECTIONS: &[&str] = &["steps", "checklist"];
+
+/// "runbook `name`" or "`name` runbook".
+static NAME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\brunbook\s+`([\w.-]+)`|`([\w.-]+)`\s+runbook\b").unwrap());
+/// A path into a `runbooks/` directory.
+static PATH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[^\w./-])((?:[\w.-]+/)*runbooks/([\w.-]+)\.md)\b").unwrap());
+/// Inline link destinations, which `check_links` already resolves.
+static LINK_TARGET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\]\([^)]*\)").unwrap());
+static NUMBERED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\d+[.)]\s").unwrap());
+static LIST_ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(?:\d+[.)]|[-*+])\s").unwrap());
+
+/// Whether `rel` is a runbook: a `.md` file directly in a `runbooks/`
+/// directory.
+pub fn is_runbook(rel: &str) -> bool {
+    let path = Path::new(rel);
+    path.extension().is_some_and(|e| e == "md")
+        && path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|n| n == "runbooks")
+}
+
+fn stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Validate a single runbook's front matter and structure.
+pub fn check_runbook(rel: &str, content: &str, config: &RunbookConfig) -> Vec<Issue> {
+    let issue = |line: usize, message: String, warning: bool| Issue {
+        file: rel.to_string(),
+        line,
+        end_line: 0,
+        message,
+        warning,
+    };
+    let mut issues = Vec::new();
+    let name = stem(rel);
+
+    let front_matter = parse_front_matter(content);
+    let body_start = front_matter.as_ref().map_or(0, |fm| fm.end_line);
+    if front_matter.is_none()
+        && content
+            .lines()
+            .next()
+            .is_some_and(|l| l.trim_end() == "---")
+    {
+        issues.push(issue(
+            1,
+            "Runbook front matter is never closed".to_string(),
+            false,
+        ));
+    }
+    if let Some(fm) = &front_matter {
+        for field in &fm.fields {
+            if !FRONT_MATTER_KEYS.contains(&field.key.as_str()) {
+                issues.push(issue(
+                    field.line,
+                    format!(
+                        "Unknown runbook front matter key `{}` (expected name, description, triggers or tags)",
+                        field.key
+                    ),
+                    true,
+                ));
+            }
+        }
+        if let Some(field) = fm.field("name")
+            && field.value != name
+        {
+            issues.push(issue(
+                field.line,
+                format!(
+                    "Runbook name `{}` doesn't match its file name `{}.md`",
+                    field.value, name
+                ),
+                false,
+            ));
+        }
+        if let Some(field) = fm.field("description")
+            && field.value.is_empty()
+        {
+            issues.push(issue(
+                field.line,
+                "Runbook description is empty".to_string(),
+                true,
+            ));
+        }
+    }
+
+    for (i, line) in content.lines().enumerate() {
+        if line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> ") {
+            issues.push(issue(
+                i + 1,
+                "Unresolved merge conflict marker \u{2014} review `runbooks upgrade` conflicts"
+                    .to_string(),
+                false,
+            ));
+        }
+    }
+
+    let heads: Vec<(usize, usize, &str)> = headings(content)
+        .into_iter()
+        .filter(|(line, _, _)| *line > body_start)
+        .collect();
+    let title_line = heads
+        .iter()
+        .find(|(_, level, _)| *level == 1)
+        .map(|(line, _, _)| *line);
+    if title_line.is_none() {
+        issues.push(issue(
+            body_start + 1,
+            "Runbook has no `# Title` heading".to_string(),
+            false,
+        ));
+    }
+
+    let body: Vec<(usize, &str)> = prose_lines(content)
+        .into_iter()
+        .filter(|(line, _)| *line > body_start)
+        .collect();
+    let sections: Vec<(usize, &str, String)> = heads
+        .iter()
+        .filter(|(_, level, _)| *level == 2)
+        .map(|(line, _, text)| (*line, *text, text.to_lowercase()))
+        .collect();
+    let steps = sections
+        .iter()
+        .find(|(_, _, lower)| STEPS_SECTIONS.contains(&lower.as_str()));
+    match steps {
+        Some((line, text, _)) => {
+            let end = heads
+                .iter()
+                .find(|(l, level, _)| l > line && *level <= 2)
+                .map_or(usize::MAX, |(l, _, _)| *l);
+            let has_items = body
+                .iter()
+                .any(|(l, text)| l > line && *l < end && LIST_ITEM_RE.is_match(text));
+            if !has_items {
+                issues.push(issue(
+                    *line,
+                    format!("`## {}` section has no list of steps", text),
+                    true,
+                ));
+            }
+        }
+        None if !body.iter().any(|(_, text)| NUMBERED_RE.is_match(text)) => {
+            issues.push(issue(
+                title_line.unwrap_or(body_start + 1),
+                "Runbook has no steps \u{2014} add a `## Steps` section with numbered steps"
+                    .to_string(),
+                true,
+            ));
+        }
+        None => {}
+    }
+
+    for required in &config.required_sections {
+        let wanted = required.to_lowercase();
+        let present = sections.iter().any(|(_, _, lower)| {
+            *lower == wanted
+                || (STEPS_SECTIONS.contains(&wanted.as_str())
+                    && STEPS_SECTIONS.contains(&lower.as_str()))
+        });
+        if !present {
+            issues.push(issue(
+                title_line.unwrap_or(body_start + 1),
+                format!("Runbook is missing required section `## {}`", required),
+                false,
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Cross-check runbook references in `files` against the runbooks among them.
+///
+/// Runbooks mentioned by name that don't exist, and path mentions outside
+/// links that don't resolve (from the root or the mentioning file's
+/// directory) are errors. Runbooks in [`RUNBOOK_DIR`] that no other file
+/// mentions by name, path or link are warnings; skill runbooks are checked
+/// against their `SKILL.md` instead.
+pub fn check_runbook_refs(files: &[PathBuf], root: &Path) -> Vec<Issue> {
+    let rel_of = |path: &Path| -> String {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+    let runbooks: Vec<String> = files
+        .iter()
+        .map(|f| rel_of(f))
+        .filter(|rel| is_runbook(rel))
+        .collect();
+    let names: BTreeSet<String> = runbooks.iter().map(|r| stem(r)).collect();
+    let mut referenced: HashSet<String> = HashSet::new();
+    let mut issues = Vec::new();
+
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let rel = rel_of(file);
+        let own = is_runbook(&rel).then(|| stem(&rel));
+        let dir = file.parent().unwrap_or(root);
+        for (line, text) in prose_lines(&content) {
+            for cap in NAME_RE.captures_iter(text) {
+                let raw = cap.get(1).or_else(|| cap.get(2)).unwrap().as_str();
+                let name = raw.strip_suffix(".md").unwrap_or(raw);
+                if own.as_deref() != Some(name) {
+                    referenced.insert(name.to_string());
+                }
+                if !names.contains(name) {
+                    let mut message = format!(
+                        "Runbook `{}` not found (expected {}/{}.md)",
+                        name, RUNBOOK_DIR, name
+                    );
+                    if let Some(suggestion) = suggest(name, &names) {
+                        message.push_str(&format!(" \u{2014} did you mean `{}`?", suggestion));
+                    }
+                    issues.push(Issue {
+                        file: rel.clone(),
+                        line,
+                        end_line: 0,
+                        message,
+                        warning: false,
+                    });
+                }
+            }
+            for cap in PATH_RE.captures_iter(text) {
+                if own.as_deref() != Some(&cap[2]) {
+                    referenced.insert(cap[2].to_string());
+                }
+            }
+            let unlinked = LINK_TARGET_RE.replace_all(text, "]()");
+            for cap in PATH_RE.captures_iter(&unlinked) {
+                let path = &cap[1];
+                if !root.join(path).is_file() && !dir.join(path).is_file() {
+                    issues.push(Issue {
+                        file: rel.clone(),
+                        line,
+                        end_line: 0,
+                        message: format!("Runbook `{}` not found", path),
+                        warning: false,
+                    });
+                }
+            }
+        }
+    }
+
+    for rel in &runbooks {
+        if Path::new(rel).parent() == Some(Path::new(RUNBOOK_DIR))
+            && !referenced.contains(&stem(rel))
+        {
+            issues.push(Issue {
+                file: rel.clone(),
+                line: 0,
+                end_line: 0,
+                message: format!(
+                    "Runbook isn't referenced by any instruction file \u{2014} mention it (see runbook `{}`) or remove it",
+                    stem(rel)
+                ),
+                warning: true,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Closest existing runbook name to `name`, if any is close enough.
+fn suggest<'a>(name: &str, names: &'a BTreeSet<String>) -> Option<&'a str> {
+    names
+        .iter()
+        .map(|n| (strsim::normalized_damerau_levenshtein(name, n), n))
+        .filter(|(score, _)| *score >= 0.7)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, n)| n.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runbooks::init::BUNDLED;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn messages(issues: &[Issue]) -> Vec<(usize, &str, bool)> {
+        issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str(), i.warning))
+            .collect()
+    }
+
+    #[test]
+    fn bundled_runbooks_are_valid() {
+        for runbook in BUNDLED {
+            let rel = format!("{}/{}", RUNBOOK_DIR, runbook.name);
+            assert!(is_runbook(&rel));
+            let issues = check_runbook(&rel, runbook.content, &RunbookConfig::default());
+            assert!(issues.is_empty(), "{}: {}", runbook.name, issues[0].message);
+        }
+        assert!(is_runbook(".claude/skills/x/runbooks/deploy.md"));
+        assert!(!is_runbook("docs/runbooks.md"));
+    }
+
+    #[test]
+    fn structure_and_front_matter() {
+        let content = "\
+---
+name: deploy
+owner: ops
+description:
+---
+Intro text.
+
+## Steps
+
+Ask first.
+";
+        let config = RunbookConfig {
+            required_sections: vec!["Preconditions".to_string(), "Steps".to_string()],
+            ..Default::default()
+        };
+        let issues = check_runbook(".agent/runbooks/release.md", content, &config);
+        assert_eq!(
+            messages(&issues),
+            vec![
+                (
+                    3,
+                    "Unknown runbook front matter key `owner` (expected name, description, triggers or tags)",
+                    true
+                ),
+                (
+                    2,
+                    "Runbook name `deploy` doesn't match its file name `release.md`",
+                    false
+                ),
+                (4, "Runbook description is empty", true),
+                (6, "Runbook has no `# Title` heading", false),
+                (8, "`## Steps` section has no list of steps", true),
+                (
+                    6,
+                    "Runbook is missing required section `## Preconditions`",
+                    false
+                ),
+            ]
+        );
+
+        let issues = check_runbook(
+            ".agent/runbooks/release.md",
+            "---\nname: release\n",
+            &RunbookConfig::default(),
+        );
+        assert_eq!(issues[0].message, "Runbook front matter is never closed");
+
+        let issues = check_runbook(
+            ".agent/runbooks/release.md",
+            "# Release\n\nJust prose.\n",
+            &RunbookConfig::default(),
+        );
+        assert_eq!(
+            messages(&issues),
+            vec![(
+                1,
+                "Runbook has no steps \u{2014} add a `## Steps` section with numbered steps",
+                true
+            )]
+        );
+
+        let issues = check_runbook(
+            ".agent/runbooks/release.md",
+            "# Release\n\n<<<<<<< local\n1. Ours\n=======\n1. Theirs\n>>>>>>> bundled\n",
+            &RunbookConfig::default(),
+        );
+        let lines: Vec<usize> = issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec![3, 7]);
+    }
+
+    #[test]
+    fn references_and_orphans() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let dir = root.join(RUNBOOK_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["precommit", "prerelease", "deploy"] {
+            fs::write(
+                dir.join(format!("{}.md", name)),
+                format!("# {}\n\n1. Go\n", name),
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.join("prerelease.md"),
+            "# Prerelease\n\n1. Run the `precommit` runbook first.\n2. See runbook `prerelease`.\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("AGENTS.md"),
+            "\
+Before committing, see runbook `precommit`.
+Release with [the runbook](.agent/runbooks/prerelease.md).
+Then follow runbook `precomit` and `.agent/runbooks/rollback.md`.
+
+```sh
+cat .agent/runbooks/missing.md
+```
+",
+        )
+        .unwrap();
+        let files: Vec<PathBuf> = [
+            "AGENTS.md",
+            ".agent/runbooks/deploy.md",
+            ".agent/runbooks/precommit.md",
+            ".agent/runbooks/prerelease.md",
+        ]
+        .iter()
+        .map(|f| root.join(f))
+        .collect();
+
+        let issues = check_runbook_refs(&files, root);
+        assert_eq!(
+            messages(&issues),
+            vec![
+                (
+                    3,
+                    "Runbook `precomit` not found (expected .agent/runbooks/precomit.md) \u{2014} did you mean `precommit`?",
+                    false
+                ),
+                (3, "Runbook `.agent/runbooks/rollback.md` not found", false),
+                (
+                    0,
+                    "Runbook isn't referenced by any instruction file \u{2014} mention it (see runbook `deploy`) or remove it",
+                    true
+                ),
+            ]
+        );
+        assert_eq!(issues[2].file, ".agent/runbooks/deploy.md");
+    }
+}
@@ -0,0 +1,267 @@
+//! Moving procedures out of instruction files into runbooks.
+//!
+//! Long step-by-step sections are loaded into every session but only
+//! needed occasionally; as runbooks they're read on demand.
+//! [`extract_runbook`] moves one section into `.agent/runbooks/<slug>.md`
+//! and leaves a one-line pointer in its place, and [`runbook_candidates`]
+//! proposes sections worth moving.
+
+use crate::links::{Link, resolve};
+use crate::markdown::{headings, prose_lines, slugify};
+use crate::runbook_audit::RUNBOOK_DIR;
+use anyhow::{Result, bail};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+/// A top-level numbered list item (`1. Do this`).
+static STEP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s{0,3}\d+[.)]\s").unwrap());
+static LINK_DEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\]\()([^)\s]+)").unwrap());
+
+/// A heading and the lines under it, up to the next heading of the same or
+/// a higher level. Trailing blank lines are excluded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub title: String,
+    pub level: usize,
+    /// 1-based line of the heading.
+    pub start: usize,
+    /// 1-based last line of the section.
+    pub end: usize,
+    /// Top-level numbered list items in the section.
+    pub steps: usize,
+}
+
+impl Section {
+    /// Lines spanned, heading included.
+    pub fn lines(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+fn sections(content: &str) -> Vec<Section> {
+    let lines: Vec<&str> = content.lines().collect();
+    let heads = headings(content);
+    let prose = prose_lines(content);
+    heads
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, level, title))| {
+            let mut end = heads[i + 1..]
+                .iter()
+                .find(|(_, l, _)| *l <= level)
+                .map_or(lines.len(), |(line, _, _)| line - 1);
+            while end > start && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            let steps = prose
+                .iter()
+                .filter(|(n, text)| *n > start && *n <= end && STEP_RE.is_match(text))
+                .count();
+            Section {
+                title: title.to_string(),
+                level,
+                start,
+                end,
+                steps,
+            }
+        })
+        .collect()
+}
+
+/// File name stem for a runbook extracted from the section `title`.
+pub fn runbook_slug(title: &str) -> String {
+    slugify(title)
+        .split(['-', '_'])
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Procedure sections worth moving to a runbook: below the document title,
+/// at least two numbered steps and at least `min_lines` lines. Sections
+/// inside an earlier candidate aren't repeated.
+pub fn runbook_candidates(content: &str, min_lines: usize) -> Vec<Section> {
+    let mut found: Vec<Section> = Vec::new();
+    for section in sections(content) {
+        if section.level < 2 || section.steps < 2 || section.lines() < min_lines {
+            continue;
+        }
+        if found.last().is_some_and(|s| section.start <= s.end) {
+            continue;
+        }
+        found.push(section);
+    }
+    found
+}
+
+/// Result of [`extract_runbook`].
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    /// The section that was moved.
+    pub section: Section,
+    /// Runbook file stem (`release-process`).
+    pub slug: String,
+    /// Content for `.agent/runbooks/<slug>.md`.
+    pub runbook: String,
+    /// `content` with the section replaced by a pointer.
+    pub content: String,
+}
+
+/// Move the section titled `title` (case-insensitive) out of `content`.
+///
+/// `rel` is the file's path relative to the project root. Subheadings are
+/// promoted so the section heading becomes the runbook's `# Title`, and
+/// relative links are rewritten to resolve from the runbook directory.
+pub fn extract_runbook(rel: &str, content: &str, title: &str) -> Result<Extraction> {
+    let all = sections(content);
+    let matches: Vec<&Section> = all
+        .iter()
+        .filter(|s| s.title.eq_ignore_ascii_case(title.trim()))
+        .collect();
+    let section = match matches.as_slice() {
+        [] => bail!("no section titled \"{}\" in {}", title, rel),
+        [section] => (*section).clone(),
+        _ => bail!(
+            "{} sections titled \"{}\" in {}; rename one first",
+            matches.len(),
+            title,
+            rel
+        ),
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let nested: Vec<(usize, usize)> = headings(content)
+        .into_iter()
+        .filter(|(n, _, _)| *n > section.start && *n <= section.end)
+        .map(|(n, level, _)| (n, level))
+        .collect();
+    let mut body: Vec<String> = Vec::new();
+    for (i, line) in lines[section.start..section.end].iter().enumerate() {
+        let n = section.start + i + 1;
+        let line = match nested.iter().find(|(h, _)| *h == n) {
+            Some((_, level)) => format!(
+                "{}{}",
+                "#".repeat(level - section.level + 1),
+                &line[*level..]
+            ),
+            None => relink(rel, line),
+        };
+        body.push(line);
+    }
+    while body.first().is_some_and(|l| l.trim().is_empty()) {
+        body.remove(0);
+    }
+
+    let slug = runbook_slug(&section.title);
+    let runbook_rel = format!("{}/{}.md", RUNBOOK_DIR, slug);
+    let depth = Path::new(rel)
+        .parent()
+        .map_or(0, |p| p.components().count());
+    let pointer = format!(
+        "{}: see runbook `{}` ([{}]({}{})).",
+        section.title,
+        slug,
+        runbook_rel,
+        "../".repeat(depth),
+        runbook_rel
+    );
+
+    let mut updated: Vec<&str> = lines[..section.start - 1].to_vec();
+    updated.push(&pointer);
+    updated.extend(&lines[section.end..]);
+    let mut content_out = updated.join("\n");
+    if content.ends_with('\n') {
+        content_out.push('\n');
+    }
+
+    Ok(Extraction {
+        runbook: format!("# {}\n\n{}\n", section.title, body.join("\n")),
+        section,
+        slug,
+        content: content_out,
+    })
+}
+
+/// Rewrite relative link destinations on `line` from `rel`'s directory to
+/// the runbook directory.
+fn relink(rel: &str, line: &str) -> String {
+    let up = "../".repeat(Path::new(RUNBOOK_DIR).components().count());
+    LINK_DEST_RE
+        .replace_all(line, |caps: &regex::Captures| {
+            let target = &caps[2];
+            let link = Link {
+                line: 0,
+                target: target.to_string(),
+                image: false,
+            };
+            if link.is_absolute_url() || target.starts_with(['#', '/']) {
+                return caps[0].to_string();
+            }
+            let (path, fragment) = match target.split_once('#') {
+                Some((p, f)) => (p, format!("#{}", f)),
+                None => (target, String::new()),
+            };
+            match resolve(Path::new(rel), path) {
+                Some(resolved) => {
+                    format!("{}{}{}{}", &caps[1], up, resolved.display(), fragment)
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE: &str = "\
+# Project
+
+Use `cargo test`.
+
+## Release process
+
+Before you start, read [the changelog](CHANGELOG.md#unreleased).
+
+### Steps
+
+1. Bump the version
+2. Tag it
+3. Push
+
+## Style
+
+- Keep it short.
+";
+
+    #[test]
+    fn extract_moves_section_and_leaves_pointer() {
+        let ex = extract_runbook("docs/CLAUDE.md", CLAUDE, "release PROCESS").unwrap();
+        assert_eq!(ex.slug, "release-process");
+        assert_eq!(ex.section.start, 5);
+        assert_eq!(ex.section.end, 13);
+        assert_eq!(
+            ex.runbook,
+            "# Release process\n\nBefore you start, read [the changelog](../../docs/CHANGELOG.md#unreleased).\n\n## Steps\n\n1. Bump the version\n2. Tag it\n3. Push\n"
+        );
+        assert_eq!(
+            ex.content,
+            "# Project\n\nUse `cargo test`.\n\nRelease process: see runbook `release-process` ([.agent/runbooks/release-process.md](../.agent/runbooks/release-process.md)).\n\n## Style\n\n- Keep it short.\n"
+        );
+
+        assert!(extract_runbook("CLAUDE.md", CLAUDE, "Deploy").is_err());
+        assert_eq!(runbook_slug("Release process (v2)!"), "release-process-v2");
+    }
+
+    #[test]
+    fn candidates_are_long_numbered_sections() {
+        let found = runbook_candidates(CLAUDE, 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title, "Release process");
+        assert_eq!(found[0].steps, 3);
+        assert_eq!(found[0].lines(), 9);
+        assert!(runbook_candidates(CLAUDE, 10).is_empty());
+    }
+}
@@ -0,0 +1,418 @@
+//! Upgrading scaffolded runbooks to newer bundled defaults.
+//!
+//! [`scaffold_runbooks`] records, for every bundled runbook it installs, the
+//! instruction-files version, a content hash and the content itself in
+//! [`BUNDLED_LOCK`]. When a newer release bundles different content,
+//! [`upgrade_runbooks`] three-way-merges it into the local copy using the
+//! recorded content as the base: untouched runbooks are replaced,
+//! customizations are kept, and overlapping edits are written as conflict
+//! markers for review.
+
+use crate::runbook_audit::RUNBOOK_DIR;
+use crate::runbooks::init::BUNDLED;
+use crate::runbooks::init_runbooks;
+use crate::types::Issue;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Scaffold record, relative to the project root.
+pub const BUNDLED_LOCK: &str = ".agent/runbooks/bundled.toml";
+
+const LOCK_HEADER: &str = "# Bundled runbooks as scaffolded or last upgraded by instruction-files.\n# `instruction-files runbooks upgrade` merges against `base`; don't edit.\n\n";
+
+/// Version recorded for content bundled with this build.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A bundled runbook as it was last written to the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledRecord {
+    /// File name in the runbook directory (`precommit.md`).
+    pub name: String,
+    /// instruction-files version that bundled the content.
+    pub version: String,
+    /// [`content_hash`] of `base`.
+    pub hash: String,
+    /// The bundled content; the merge base for the next upgrade.
+    pub base: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BundledLock {
+    #[serde(default)]
+    runbook: Vec<BundledRecord>,
+}
+
+impl BundledLock {
+    fn load(root: &Path) -> Result<Self> {
+        let path = root.join(BUNDLED_LOCK);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    fn save(&self, root: &Path) -> Result<()> {
+        let path = root.join(BUNDLED_LOCK);
+        let content = toml::to_string(self).context("failed to serialize runbook records")?;
+        std::fs::write(&path, format!("{}{}", LOCK_HEADER, content))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn get(&self, name: &str) -> Option<&BundledRecord> {
+        self.runbook.iter().find(|r| r.name == name)
+    }
+
+    fn set(&mut self, name: &str, content: &str) {
+        let record = BundledRecord {
+            name: name.to_string(),
+            version: VERSION.to_string(),
+            hash: content_hash(content),
+            base: content.to_string(),
+        };
+        match self.runbook.iter_mut().find(|r| r.name == name) {
+            Some(existing) => *existing = record,
+            None => self.runbook.push(record),
+        }
+    }
+}
+
+/// Stable 64-bit FNV-1a hash of `content`, as hex.
+pub fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in content.bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// [`init_runbooks`] plus a [`BUNDLED_LOCK`] record for every runbook that
+/// matches the bundled content and isn't recorded yet.
+///
+/// Returns the number of runbooks written.
+pub fn scaffold_runbooks(root: &Path) -> Result<usize> {
+    let written = init_runbooks(root)?;
+    let mut lock = BundledLock::load(root)?;
+    let mut changed = false;
+    for runbook in BUNDLED {
+        let path = root.join(RUNBOOK_DIR).join(runbook.name);
+        if lock.get(runbook.name).is_none()
+            && std::fs::read_to_string(&path).is_ok_and(|c| c == runbook.content)
+        {
+            lock.set(runbook.name, runbook.content);
+            changed = true;
+        }
+    }
+    if changed {
+        lock.save(root)?;
+    }
+    Ok(written)
+}
+
+/// Outcome of upgrading one bundled runbook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeStatus {
+    /// Already matches the bundled version.
+    Current,
+    /// A newer bundled version exists (check mode only).
+    Outdated,
+    /// Unmodified locally; replaced with the bundled version.
+    Updated,
+    /// Local customizations merged cleanly with the bundled changes.
+    Merged,
+    /// Merged with this many conflict regions marked for review.
+    Conflicted(usize),
+    /// Differs from the bundled content and has no scaffold record, so
+    /// there is no base to merge against.
+    Untracked,
+}
+
+/// Upgrade every scaffolded bundled runbook under `root`.
+///
+/// Runbooks that were never scaffolded (or were deleted) are left alone.
+/// With `write` false nothing is changed and runbooks that would change
+/// are reported as [`UpgradeStatus::Outdated`].
+pub fn upgrade_runbooks(root: &Path, write: bool) -> Result<Vec<(String, UpgradeStatus)>> {
+    let bundled: Vec<(&str, &str)> = BUNDLED.iter().map(|r| (r.name, r.content)).collect();
+    upgrade_with(root, &bundled, write)
+}
+
+fn upgrade_with(
+    root: &Path,
+    bundled: &[(&str, &str)],
+    write: bool,
+) -> Result<Vec<(String, UpgradeStatus)>> {
+    let mut lock = BundledLock::load(root)?;
+    let mut results = Vec::new();
+    let mut changed = false;
+
+    for (name, content) in bundled {
+        let path = root.join(RUNBOOK_DIR).join(name);
+        let Ok(local) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let rel = format!("{}/{}", RUNBOOK_DIR, name);
+        let status = match lock.get(name) {
+            Some(record) if record.hash == content_hash(content) => UpgradeStatus::Current,
+            None if local == *content => {
+                if write {
+                    lock.set(name, content);
+                    changed = true;
+                }
+                UpgradeStatus::Current
+            }
+            None => UpgradeStatus::Untracked,
+            Some(_) if !write => UpgradeStatus::Outdated,
+            Some(record) => {
+                let (merged, status) = if local == record.base || local == *content {
+                    (content.to_string(), UpgradeStatus::Updated)
+                } else {
+                    match merge3(&record.base, &local, content) {
+                        (merged, 0) => (merged, UpgradeStatus::Merged),
+                        (merged, n) => (merged, UpgradeStatus::Conflicted(n)),
+                    }
+                };
+                std::fs::write(&path, merged)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                lock.set(name, content);
+                changed = true;
+                status
+            }
+        };
+        results.push((rel, status));
+    }
+
+    if changed {
+        lock.save(root)?;
+    }
+    Ok(results)
+}
+
+/// Report scaffolded runbooks whose bundled version has changed, and
+/// customized copies of bundled runbooks that have no scaffold record.
+pub fn check_runbook_upgrades(root: &Path) -> Vec<Issue> {
+    let Ok(results) = upgrade_runbooks(root, false) else {
+        return vec![Issue {
+            file: BUNDLED_LOCK.to_string(),
+            line: 0,
+            end_line: 0,
+            message: "Runbook scaffold record can't be parsed".to_string(),
+            warning: false,
+        }];
+    };
+    results
+        .into_iter()
+        .filter_map(|(rel, status)| {
+            let message = match status {
+                UpgradeStatus::Outdated => "A newer bundled version of this runbook is available \u{2014} run `instruction-files runbooks upgrade`",
+                UpgradeStatus::Untracked => "Customized bundled runbook has no scaffold record, so upgrades can't merge into it \u{2014} move it aside, run `instruction-files init`, and reapply your changes",
+                _ => return None,
+            };
+            Some(Issue {
+                file: rel,
+                line: 0,
+                end_line: 0,
+                message: message.to_string(),
+                warning: true,
+            })
+        })
+        .collect()
+}
+
+/// Longest common subsequence of `a` and `b` as matched index pairs.
+fn lcs(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    let mut table = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::new();
+    while i < n && j < m {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// For each line of `base`, the matching line index in `other`, if any.
+fn base_map(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut map = vec![None; base.len()];
+    for (i, j) in lcs(base, other) {
+        map[i] = Some(j);
+    }
+    map
+}
+
+/// Line-based three-way merge of `ours` and `theirs` against `base`.
+///
+/// Returns the merged text and the number of conflict regions, which are
+/// written with `<<<<<<<`/`=======`/`>>>>>>>` markers.
+fn merge3(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+    let o: Vec<&str> = base.lines().collect();
+    let a: Vec<&str> = ours.lines().collect();
+    let b: Vec<&str> = theirs.lines().collect();
+    let ma = base_map(&o, &a);
+    let mb = base_map(&o, &b);
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut conflicts = 0;
+    let ours_marker = "<<<<<<< local";
+    let theirs_marker = format!(">>>>>>> bundled (instruction-files {})", VERSION);
+    let (mut io, mut ia, mut ib) = (0, 0, 0);
+    loop {
+        let stable = (io..o.len()).find(|&k| ma[k].is_some() && mb[k].is_some());
+        let (eo, ea, eb) = match stable {
+            Some(k) => (k, ma[k].unwrap(), mb[k].unwrap()),
+            None => (o.len(), a.len(), b.len()),
+        };
+        let (co, ca, cb) = (&o[io..eo], &a[ia..ea], &b[ib..eb]);
+        if ca == co {
+            out.extend(cb);
+        } else if cb == co || ca == cb {
+            out.extend(ca);
+        } else {
+            conflicts += 1;
+            out.push(ours_marker);
+            out.extend(ca);
+            out.push("=======");
+            out.extend(cb);
+            out.push(&theirs_marker);
+        }
+        if stable.is_none() {
+            break;
+        }
+        out.push(o[eo]);
+        (io, ia, ib) = (eo + 1, ea + 1, eb + 1);
+    }
+
+    let mut merged = out.join("\n");
+    if ours.ends_with('\n') || theirs.ends_with('\n') {
+        merged.push('\n');
+    }
+    (merged, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn merge_keeps_both_sides() {
+        let base = "# Precommit\n\n1. Test\n2. Lint\n3. Review\n";
+        let ours = "# Precommit\n\n1. Test with `make test`\n2. Lint\n3. Review\n4. Ask ops\n";
+        let theirs = "# Precommit\n\n1. Test\n2. Lint\n3. Review the diff\n";
+        let (merged, n) = merge3(base, ours, theirs);
+        assert_eq!(n, 1);
+        assert_eq!(
+            merged,
+            format!(
+                "# Precommit\n\n1. Test with `make test`\n2. Lint\n<<<<<<< local\n3. Review\n4. Ask ops\n=======\n3. Review the diff\n>>>>>>> bundled (instruction-files {})\n",
+                VERSION
+            )
+        );
+
+        let theirs = "# Precommit\n\n1. Test\n2. Lint and format\n3. Review\n";
+        let ours = "# Precommit (acme)\n\n1. Test\n2. Lint\n3. Review\n4. Ask ops\n";
+        assert_eq!(
+            merge3(base, ours, theirs),
+            (
+                "# Precommit (acme)\n\n1. Test\n2. Lint and format\n3. Review\n4. Ask ops\n"
+                    .to_string(),
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn scaffold_records_and_upgrade_merges() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        assert_eq!(scaffold_runbooks(root).unwrap(), BUNDLED.len());
+        let lock = BundledLock::load(root).unwrap();
+        assert_eq!(lock.runbook.len(), BUNDLED.len());
+        assert_eq!(lock.runbook[0].hash, content_hash(BUNDLED[0].content));
+        assert!(check_runbook_upgrades(root).is_empty());
+
+        let v1 = "# Precommit\n\n## Checklist\n\n1. Test\n2. Review\n";
+        let v2 = "# Precommit\n\n## Checklist\n\n1. Test\n2. Lint\n3. Review\n";
+        let dir = root.join(RUNBOOK_DIR);
+        let ours = v1.replace("# Precommit", "# Precommit (acme)");
+        fs::write(dir.join("precommit.md"), &ours).unwrap();
+        fs::write(dir.join("prerelease.md"), v1).unwrap();
+        let mut lock = BundledLock::default();
+        lock.set("precommit.md", v1);
+        lock.set("prerelease.md", v1);
+        lock.save(root).unwrap();
+
+        let bundled = [("precommit.md", v2), ("prerelease.md", v2)];
+        let check = upgrade_with(root, &bundled, false).unwrap();
+        assert!(check.iter().all(|(_, s)| *s == UpgradeStatus::Outdated));
+
+        let results = upgrade_with(root, &bundled, true).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                (
+                    ".agent/runbooks/precommit.md".to_string(),
+                    UpgradeStatus::Merged
+                ),
+                (
+                    ".agent/runbooks/prerelease.md".to_string(),
+                    UpgradeStatus::Updated
+                ),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("precommit.md")).unwrap(),
+            v2.replace("# Precommit", "# Precommit (acme)")
+        );
+        assert_eq!(fs::read_to_string(dir.join("prerelease.md")).unwrap(), v2);
+        let again = upgrade_with(root, &bundled, false).unwrap();
+        assert!(again.iter().all(|(_, s)| *s == UpgradeStatus::Current));
+    }
+
+    #[test]
+    fn unrecorded_customizations_are_untracked() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let dir = root.join(RUNBOOK_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("precommit.md"), "# Ours\n").unwrap();
+        let results = upgrade_with(root, &[("precommit.md", "# Theirs\n")], true).unwrap();
+        assert_eq!(results[0].1, UpgradeStatus::Untracked);
+        assert_eq!(
+            fs::read_to_string(dir.join("precommit.md")).unwrap(),
+            "# Ours\n"
+        );
+        assert!(!root.join(BUNDLED_LOCK).exists());
+
+        fs::write(dir.join(BUNDLED[0].name), "# Ours\n").unwrap();
+        let issues = check_runbook_upgrades(root);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].file,
+            format!("{}/{}", RUNBOOK_DIR, BUNDLED[0].name)
+        );
+        assert!(issues[0].warning);
+        assert!(issues[0].message.contains("run `instruction-files init`"));
+    }
+}
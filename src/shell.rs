@@ -0,0 +1,1022 @@
+//! Shell snippet linting for instruction files.
+//!
+//! `sh`, `bash`, `shell` and `console` blocks are tokenized and parsed with a
+//! small POSIX/bash parser — quoting, expansions, here-documents, `if`/`fi`,
+//! loops, `case`/`esac`, groups and subshells — to catch syntax errors before
+//! an agent runs them. Parsed commands are then checked for references to
+//! missing scripts (`./scripts/foo.sh`) and risky constructs such as
+//! unquoted variables in `rm` arguments.
+//!
+//! In `console` blocks only `$ ` prompt lines (and their `\` or `> `
+//! continuations) are commands; everything else is output.
+
+use crate::markdown::{CodeBlock, code_blocks};
+use crate::syntax::OPT_OUT;
+use crate::types::Issue;
+use std::path::Path;
+
+/// Fence languages linted as shell. `zsh` is left out: its syntax diverges.
+const LINT_LANGS: &[&str] = &["sh", "bash", "shell", "console", "shell-session"];
+
+/// Commands that run the next word as a command.
+const PREFIX_COMMANDS: &[&str] = &["sudo", "env", "exec", "command", "time", "nohup", "xargs"];
+
+/// A word in a parsed command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Word {
+    /// Source text, quotes included.
+    pub raw: String,
+    /// Contains quotes or escapes (so it can't be a reserved word).
+    pub quoted: bool,
+    /// Contains a `$var`, `${...}` or `$(...)` expansion outside double quotes.
+    pub unquoted_expansion: bool,
+}
+
+impl Word {
+    /// Text with quote characters removed.
+    pub fn unquoted(&self) -> String {
+        self.raw.replace(['"', '\''], "")
+    }
+}
+
+/// A simple command: its words (redirections excluded) and starting line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SimpleCommand {
+    pub line: usize,
+    pub words: Vec<Word>,
+}
+
+impl SimpleCommand {
+    /// The command name and its arguments, skipping `VAR=value` assignments
+    /// and wrappers like `sudo` or `env`.
+    pub fn argv(&self) -> &[Word] {
+        let mut i = 0;
+        while i < self.words.len() {
+            let w = &self.words[i].raw;
+            let assignment = w
+                .split_once('=')
+                .is_some_and(|(name, _)| is_name(name) && !name.is_empty());
+            if assignment
+                || PREFIX_COMMANDS.contains(&w.as_str())
+                || (i > 0
+                    && w.starts_with('-')
+                    && PREFIX_COMMANDS.contains(&self.words[i - 1].raw.as_str()))
+            {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        &self.words[i..]
+    }
+}
+
+/// A shell syntax error at a line in the instruction file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+fn is_name(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Lines of a shell block that form the script, as `(line_number, text)`.
+///
+/// Returns `None` for non-shell blocks and blocks opted out with `invalid`.
+/// Prompted blocks (`console`, or any block with a `$ ` line) keep only
+/// prompt lines and their continuations, with the prompt stripped.
+pub(crate) fn shell_script<'a>(block: &CodeBlock<'a>) -> Option<Vec<(usize, &'a str)>> {
+    let lang = block.lang();
+    if !LINT_LANGS.contains(&lang.as_str()) || block.info.split_whitespace().any(|w| w == OPT_OUT) {
+        return None;
+    }
+    let prompted = lang == "console"
+        || lang == "shell-session"
+        || block
+            .lines
+            .iter()
+            .any(|(_, l)| l.trim_start().starts_with("$ "));
+    if !prompted {
+        return Some(block.lines.clone());
+    }
+    let mut script = Vec::new();
+    // The command so far, while it continues onto the next line.
+    let mut command: Option<String> = None;
+    for (n, line) in &block.lines {
+        let trimmed = line.trim_start();
+        let text = if let Some(rest) = trimmed.strip_prefix("$ ") {
+            command = Some(String::new());
+            rest
+        } else if let Some(cmd) = command.as_mut() {
+            cmd.push('\n');
+            trimmed.strip_prefix("> ").unwrap_or(line)
+        } else if trimmed == "$" {
+            ""
+        } else {
+            continue;
+        };
+        let cmd = command.get_or_insert_with(String::new);
+        cmd.push_str(text);
+        if !(cmd.ends_with('\\') || open_quote(cmd)) {
+            command = None;
+        }
+        script.push((*n, text));
+    }
+    Some(script)
+}
+
+/// Whether a line leaves a quote open (the command continues on a `> ` line).
+fn open_quote(line: &str) -> bool {
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '\\') | (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') => break,
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(Word),
+    Op(&'static str),
+    Newline,
+}
+
+const REDIRECTS: &[&str] = &[
+    "<<-", "<<<", "<<", "<&", "<>", "<", ">>", ">&", ">|", ">", "&>>", "&>",
+];
+
+struct Lexer<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    /// Index into `lines` of the line at `pos`.
+    line: usize,
+    lines: &'a [usize],
+    /// Here-documents to read after the next newline: `(delimiter, line)`.
+    heredocs: Vec<(String, usize)>,
+    tokens: Vec<(usize, Token)>,
+}
+
+impl Lexer<'_> {
+    fn file_line(&self, idx: usize) -> usize {
+        self.lines[idx.min(self.lines.len() - 1)]
+    }
+
+    fn error(&self, idx: usize, message: impl Into<String>) -> SyntaxError {
+        SyntaxError {
+            line: self.file_line(idx),
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn push(&mut self, line: usize, token: Token) {
+        let line = self.file_line(line);
+        self.tokens.push((line, token));
+    }
+
+    fn run(mut self) -> Result<Vec<(usize, Token)>, SyntaxError> {
+        while let Some(c) = self.peek(0) {
+            let line = self.line;
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.bump();
+                }
+                '\\' if self.peek(1) == Some('\n') => {
+                    self.bump();
+                    self.bump();
+                }
+                '#' => {
+                    while self.peek(0).is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                '\n' => {
+                    self.bump();
+                    self.push(line, Token::Newline);
+                    self.read_heredocs()?;
+                }
+                ';' | '&' | '|' | '<' | '>' => self.operator(line)?,
+                '(' if self.peek(1) == Some('(') => {
+                    let start = self.pos;
+                    self.balanced('(', ')', line)?;
+                    let raw: String = self.chars[start..self.pos].iter().collect();
+                    let word = Word {
+                        raw,
+                        quoted: true,
+                        unquoted_expansion: false,
+                    };
+                    self.push(line, Token::Word(word));
+                }
+                '(' => {
+                    self.bump();
+                    self.push(line, Token::Op("("));
+                }
+                ')' => {
+                    self.bump();
+                    self.push(line, Token::Op(")"));
+                }
+                _ => {
+                    let word = self.word()?;
+                    self.push(line, Token::Word(word));
+                }
+            }
+        }
+        if let Some((delim, line)) = self.heredocs.first() {
+            return Err(self.error(
+                *line,
+                format!("unterminated here-document (missing `{}`)", delim),
+            ));
+        }
+        Ok(self.tokens)
+    }
+
+    fn operator(&mut self, line: usize) -> Result<(), SyntaxError> {
+        // `<name>` and `<example name>` are documentation placeholders, not
+        // redirections: a letter right after `<`, no space right before `>`.
+        if self.peek(0) == Some('<') && self.peek(1).is_some_and(|c| c.is_ascii_alphabetic()) {
+            let len = self.chars[self.pos + 1..]
+                .iter()
+                .take_while(|c| !matches!(c, '>' | '<' | '\n' | '\'' | '"' | '$'))
+                .count();
+            let before_close = self.chars[self.pos + len];
+            if self.peek(len + 1) == Some('>') && !before_close.is_whitespace() {
+                let raw: String = self.chars[self.pos..self.pos + len + 2].iter().collect();
+                self.pos += len + 2;
+                let word = Word {
+                    raw,
+                    quoted: true,
+                    unquoted_expansion: false,
+                };
+                self.push(line, Token::Word(word));
+                return Ok(());
+            }
+        }
+        // Process substitution.
+        if matches!(self.peek(0), Some('<' | '>')) && self.peek(1) == Some('(') {
+            let start = self.pos;
+            self.bump();
+            self.balanced('(', ')', line)?;
+            let raw: String = self.chars[start..self.pos].iter().collect();
+            let word = Word {
+                raw,
+                quoted: false,
+                unquoted_expansion: true,
+            };
+            self.push(line, Token::Word(word));
+            return Ok(());
+        }
+        const OPS: &[&str] = &[
+            ";;&", ";;", ";&", ";", "&&", "||", "|&", "|", "&>>", "&>", "&", "<<-", "<<<", "<<",
+            "<&", "<>", "<", ">>", ">&", ">|", ">",
+        ];
+        let op = OPS
+            .iter()
+            .find(|op| op.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c)))
+            .copied()
+            .unwrap_or(";");
+        self.pos += op.len();
+        self.push(line, Token::Op(op));
+        if op == "<<" || op == "<<-" {
+            while matches!(self.peek(0), Some(' ' | '\t')) {
+                self.bump();
+            }
+            if self.peek(0).is_some_and(|c| !"\n;&|<>()".contains(c)) {
+                let word = self.word()?;
+                self.heredocs
+                    .push((word.unquoted().replace('\\', ""), line));
+                self.push(line, Token::Word(word));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_heredocs(&mut self) -> Result<(), SyntaxError> {
+        for (delim, line) in std::mem::take(&mut self.heredocs) {
+            loop {
+                if self.pos >= self.chars.len() {
+                    return Err(self.error(
+                        line,
+                        format!("unterminated here-document (missing `{}`)", delim),
+                    ));
+                }
+                let start = self.pos;
+                while self.peek(0).is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                self.bump();
+                if text.trim_start_matches('\t').trim_end() == delim {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume from an opening delimiter to its match, honoring quotes.
+    fn balanced(&mut self, open: char, close: char, line: usize) -> Result<(), SyntaxError> {
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            match c {
+                '\\' => {
+                    self.bump();
+                }
+                '\'' => self.single_quote(line)?,
+                '"' => self.double_quote(line)?,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error(line, format!("unterminated `{}`", open)))
+    }
+
+    fn single_quote(&mut self, line: usize) -> Result<(), SyntaxError> {
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error(line, "unterminated single quote")),
+            }
+        }
+    }
+
+    fn double_quote(&mut self, line: usize) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek(0) {
+                Some('"') => {
+                    self.bump();
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.bump();
+                    self.bump();
+                }
+                Some('$') if self.peek(1) == Some('(') => {
+                    self.bump();
+                    self.balanced('(', ')', line)?;
+                }
+                Some('`') => {
+                    self.bump();
+                    self.backtick(line)?;
+                }
+                Some(_) => {
+                    self.bump();
+                }
+                None => return Err(self.error(line, "unterminated double quote")),
+            }
+        }
+    }
+
+    fn backtick(&mut self, line: usize) -> Result<(), SyntaxError> {
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some('`') => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error(line, "unterminated backquote")),
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<Word, SyntaxError> {
+        let line = self.line;
+        let start = self.pos;
+        let mut quoted = false;
+        let mut unquoted_expansion = false;
+        while let Some(c) = self.peek(0) {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | ';' | '&' | '|' | '<' | '>' | ')' => break,
+                '(' => {
+                    // `arr=(a b)` and extglob `@(a|b)` keep the parens in the word.
+                    let prev = (self.pos > start).then(|| self.chars[self.pos - 1]);
+                    if !matches!(prev, Some('=' | '@' | '?' | '*' | '+' | '!')) {
+                        break;
+                    }
+                    self.balanced('(', ')', line)?;
+                }
+                '\\' => {
+                    quoted = true;
+                    self.bump();
+                    self.bump();
+                }
+                '\'' => {
+                    quoted = true;
+                    self.bump();
+                    self.single_quote(line)?;
+                }
+                '"' => {
+                    quoted = true;
+                    self.bump();
+                    self.double_quote(line)?;
+                }
+                '`' => {
+                    unquoted_expansion = true;
+                    self.bump();
+                    self.backtick(line)?;
+                }
+                '$' => {
+                    self.bump();
+                    match self.peek(0) {
+                        Some('(') => {
+                            unquoted_expansion = true;
+                            self.balanced('(', ')', line)?;
+                        }
+                        Some('{') => {
+                            unquoted_expansion = true;
+                            self.balanced('{', '}', line)?;
+                        }
+                        Some('\'') => {
+                            quoted = true;
+                            self.bump();
+                            while let Some(c) = self.bump() {
+                                match c {
+                                    '\\' => {
+                                        self.bump();
+                                    }
+                                    '\'' => break,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Some(c) if c.is_ascii_alphanumeric() || "_@*#?$!-".contains(c) => {
+                            unquoted_expansion = true;
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        Ok(Word {
+            raw: self.chars[start..self.pos].iter().collect(),
+            quoted,
+            unquoted_expansion,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// `if`/`while` condition, `for` head.
+    Cond,
+    Body,
+    Else,
+    /// `case WORD` before `in`.
+    Head,
+    /// Patterns of a `case` arm, up to `)`.
+    Pattern,
+}
+
+struct Frame {
+    opener: &'static str,
+    line: usize,
+    stage: Stage,
+}
+
+const OPENERS: &[&str] = &["if", "while", "until", "for", "select", "case", "{"];
+
+fn closer(opener: &str) -> &'static str {
+    match opener {
+        "if" => "fi",
+        "case" => "esac",
+        "{" => "}",
+        "(" => ")",
+        _ => "done",
+    }
+}
+
+/// Parse a shell script into simple commands.
+///
+/// `lines` are `(line_number, text)` pairs; errors carry the line number of
+/// the offending token.
+pub(crate) fn parse_script(lines: &[(usize, &str)]) -> Result<Vec<SimpleCommand>, SyntaxError> {
+    if lines.is_empty() {
+        return Ok(Vec::new());
+    }
+    let text: Vec<&str> = lines.iter().map(|(_, l)| *l).collect();
+    let numbers: Vec<usize> = lines.iter().map(|(n, _)| *n).collect();
+    let lexer = Lexer {
+        chars: text.join("\n").chars().collect(),
+        pos: 0,
+        line: 0,
+        lines: &numbers,
+        heredocs: Vec::new(),
+        tokens: Vec::new(),
+    };
+    let tokens = lexer.run()?;
+
+    let unexpected = |line: usize, tok: &str| SyntaxError {
+        line,
+        message: format!("unexpected `{}`", tok),
+    };
+    let mut commands: Vec<SimpleCommand> = Vec::new();
+    let mut current: Option<SimpleCommand> = None;
+    let mut stack: Vec<Frame> = Vec::new();
+    // Expecting the start of a command (where reserved words are recognized).
+    let mut cmd_pos = true;
+    // A `&&`/`||`/`|` waiting for its right-hand command.
+    let mut pending: Option<(usize, &'static str)> = None;
+    let mut redirect: Option<(usize, &'static str)> = None;
+    let mut for_head = false;
+    // `name (` seen: the next token must be `)` of a function definition.
+    let mut fn_parens = false;
+    // After `function`: 1 = expecting the name, 2 = name seen.
+    let mut fn_keyword = 0;
+
+    let flush = |current: &mut Option<SimpleCommand>, commands: &mut Vec<SimpleCommand>| {
+        if let Some(cmd) = current.take() {
+            commands.push(cmd);
+        }
+    };
+
+    for (line, token) in tokens {
+        if let Some((rline, op)) = redirect {
+            match &token {
+                Token::Word(_) => {
+                    redirect = None;
+                    continue;
+                }
+                Token::Newline => {
+                    return Err(SyntaxError {
+                        line: rline,
+                        message: format!("missing target after `{}`", op),
+                    });
+                }
+                Token::Op(next) => return Err(unexpected(line, next)),
+            }
+        }
+        if fn_parens {
+            if token != Token::Op(")") {
+                return Err(unexpected(line, "("));
+            }
+            fn_parens = false;
+            cmd_pos = true;
+            continue;
+        }
+        if fn_keyword == 2 && token != Token::Op("(") {
+            fn_keyword = 0;
+        }
+        let stage = stack.last().map(|f| (f.opener, f.stage));
+        match token {
+            Token::Newline => {
+                if matches!(stage, Some((_, Stage::Head | Stage::Pattern))) {
+                    continue;
+                }
+                flush(&mut current, &mut commands);
+                for_head = false;
+                cmd_pos = true;
+            }
+            Token::Op(op) if REDIRECTS.contains(&op) => redirect = Some((line, op)),
+            Token::Op(op) => {
+                if matches!(stage, Some(("case", Stage::Pattern))) {
+                    match op {
+                        "|" | "(" => continue,
+                        ")" => {
+                            stack.last_mut().unwrap().stage = Stage::Body;
+                            cmd_pos = true;
+                            continue;
+                        }
+                        _ => return Err(unexpected(line, op)),
+                    }
+                }
+                match op {
+                    "&&" | "||" | "|" | "|&" => {
+                        if cmd_pos {
+                            return Err(unexpected(line, op));
+                        }
+                        flush(&mut current, &mut commands);
+                        pending = Some((line, op));
+                        cmd_pos = true;
+                        continue;
+                    }
+                    ";" | "&" => {
+                        if for_head {
+                            for_head = false;
+                        } else if cmd_pos {
+                            return Err(unexpected(line, op));
+                        }
+                        flush(&mut current, &mut commands);
+                        cmd_pos = true;
+                    }
+                    ";;" | ";&" | ";;&" => {
+                        if !matches!(stage, Some(("case", Stage::Body))) {
+                            return Err(unexpected(line, op));
+                        }
+                        flush(&mut current, &mut commands);
+                        stack.last_mut().unwrap().stage = Stage::Pattern;
+                    }
+                    "(" => {
+                        let one_word = current.as_ref().is_some_and(|c| c.words.len() == 1);
+                        if fn_keyword == 2 || (!cmd_pos && one_word) {
+                            current = None;
+                            fn_parens = true;
+                        } else if cmd_pos {
+                            stack.push(Frame {
+                                opener: "(",
+                                line,
+                                stage: Stage::Body,
+                            });
+                        } else {
+                            return Err(unexpected(line, op));
+                        }
+                    }
+                    _ => {
+                        // ")"
+                        if stage.map(|(o, _)| o) != Some("(") || pending.is_some() {
+                            return Err(unexpected(line, op));
+                        }
+                        flush(&mut current, &mut commands);
+                        stack.pop();
+                        cmd_pos = false;
+                    }
+                }
+                pending = None;
+            }
+            Token::Word(word) => {
+                match stage {
+                    Some(("case", Stage::Head)) => {
+                        if word.raw == "in" && current.is_some() {
+                            current = None;
+                            stack.last_mut().unwrap().stage = Stage::Pattern;
+                        } else {
+                            current = Some(SimpleCommand {
+                                line,
+                                words: vec![word],
+                            });
+                        }
+                        continue;
+                    }
+                    Some(("case", Stage::Pattern)) => {
+                        if word.raw == "esac" {
+                            stack.pop();
+                            cmd_pos = false;
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+                if for_head {
+                    continue;
+                }
+                if fn_keyword == 1 {
+                    // `function name` — the body (or `()`) follows.
+                    fn_keyword = 2;
+                    cmd_pos = true;
+                    continue;
+                }
+                let reserved = (cmd_pos && !word.quoted).then_some(word.raw.as_str());
+                let top_stage = stage.map(|(_, s)| s);
+                let opener = stage.map(|(o, _)| o);
+                match reserved {
+                    Some(kw) if OPENERS.contains(&kw) => {
+                        let kw = OPENERS[OPENERS.iter().position(|o| *o == kw).unwrap()];
+                        let stage = match kw {
+                            "case" => Stage::Head,
+                            "{" => Stage::Body,
+                            _ => Stage::Cond,
+                        };
+                        stack.push(Frame {
+                            opener: kw,
+                            line,
+                            stage,
+                        });
+                        for_head = kw == "for" || kw == "select";
+                        cmd_pos = kw != "case";
+                    }
+                    Some("then") if opener == Some("if") && top_stage == Some(Stage::Cond) => {
+                        stack.last_mut().unwrap().stage = Stage::Body;
+                    }
+                    Some("elif") if opener == Some("if") && top_stage == Some(Stage::Body) => {
+                        stack.last_mut().unwrap().stage = Stage::Cond;
+                    }
+                    Some("else") if opener == Some("if") && top_stage == Some(Stage::Body) => {
+                        stack.last_mut().unwrap().stage = Stage::Else;
+                    }
+                    Some("do")
+                        if matches!(opener, Some("while" | "until" | "for" | "select"))
+                            && top_stage == Some(Stage::Cond) =>
+                    {
+                        stack.last_mut().unwrap().stage = Stage::Body;
+                    }
+                    Some(kw @ ("fi" | "done" | "esac" | "}"))
+                        if opener.is_some_and(|o| closer(o) == kw)
+                            && top_stage != Some(Stage::Cond) =>
+                    {
+                        stack.pop();
+                        cmd_pos = false;
+                    }
+                    Some(kw @ ("then" | "elif" | "else" | "do" | "fi" | "done" | "esac" | "}")) => {
+                        return Err(unexpected(line, kw));
+                    }
+                    Some("function") => {
+                        fn_keyword = 1;
+                        continue;
+                    }
+                    Some("!") => {}
+                    _ => {
+                        match current.as_mut() {
+                            Some(cmd) => cmd.words.push(word),
+                            None => {
+                                current = Some(SimpleCommand {
+                                    line,
+                                    words: vec![word],
+                                })
+                            }
+                        }
+                        cmd_pos = false;
+                    }
+                }
+                pending = None;
+            }
+        }
+    }
+
+    if let Some((line, op)) = redirect {
+        return Err(SyntaxError {
+            line,
+            message: format!("missing target after `{}`", op),
+        });
+    }
+    if let Some((line, op)) = pending {
+        return Err(SyntaxError {
+            line,
+            message: format!("nothing after `{}`", op),
+        });
+    }
+    if let Some(frame) = stack.last() {
+        return Err(SyntaxError {
+            line: frame.line,
+            message: format!(
+                "`{}` without a matching `{}`",
+                frame.opener,
+                closer(frame.opener)
+            ),
+        });
+    }
+    flush(&mut current, &mut commands);
+    Ok(commands)
+}
+
+/// Script path invoked by a command, if it names one that can be checked:
+/// a relative path with a `/` that ends in `.sh`/`.bash` or lives in
+/// `scripts/`.
+fn script_ref(argv: &[Word]) -> Option<String> {
+    let name = argv.first()?.unquoted();
+    let candidate = if matches!(name.as_str(), "sh" | "bash" | "source" | ".") {
+        argv[1..]
+            .iter()
+            .find(|w| !w.raw.starts_with('-'))?
+            .unquoted()
+    } else {
+        name
+    };
+    let path = candidate.strip_prefix("./").unwrap_or(&candidate);
+    let checkable = (candidate.contains('/') || path.starts_with("scripts/"))
+        && !candidate.starts_with('/')
+        && !candidate.starts_with('~')
+        && !candidate.contains(['$', '<', '>', '{', '}', '*', '?', '['])
+        && (path.ends_with(".sh") || path.ends_with(".bash") || path.starts_with("scripts/"));
+    checkable.then_some(candidate)
+}
+
+/// Lint `sh`/`bash`/`console` code blocks.
+///
+/// Syntax errors (one per block) and missing scripts are errors; unquoted
+/// variable expansions in `rm` arguments are warnings. Scripts resolve
+/// against the instruction file's directory, then the project root.
+pub fn check_shell(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let dir = Path::new(rel).parent().unwrap_or(Path::new(""));
+    let mut issues = Vec::new();
+    let mut push = |line: usize, message: String, warning: bool| {
+        issues.push(Issue {
+            file: rel.to_string(),
+            line,
+            end_line: 0,
+            message,
+            warning,
+        });
+    };
+    for block in code_blocks(content) {
+        let Some(script) = shell_script(&block) else {
+            continue;
+        };
+        let commands = match parse_script(&script) {
+            Ok(commands) => commands,
+            Err(e) => {
+                push(e.line, format!("Shell syntax error: {}", e.message), false);
+                continue;
+            }
+        };
+        for cmd in &commands {
+            let argv = cmd.argv();
+            if let Some(script) = script_ref(argv) {
+                let path = script.strip_prefix("./").unwrap_or(&script);
+                if !root.join(dir).join(path).exists() && !root.join(path).exists() {
+                    push(cmd.line, format!("Script `{}` not found", script), false);
+                }
+            }
+            if argv.first().is_some_and(|w| w.raw == "rm") {
+                for arg in argv[1..].iter().filter(|w| w.unquoted_expansion) {
+                    push(
+                        cmd.line,
+                        format!(
+                            "Unquoted expansion `{}` in `rm` \u{2014} quote it so an empty or \
+                             spaced value can't widen the deletion",
+                            arg.raw
+                        ),
+                        true,
+                    );
+                }
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn parse(script: &str) -> Result<Vec<SimpleCommand>, SyntaxError> {
+        let lines: Vec<(usize, &str)> = script
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l))
+            .collect();
+        parse_script(&lines)
+    }
+
+    fn error(script: &str) -> (usize, String) {
+        let e = parse(script).unwrap_err();
+        (e.line, e.message)
+    }
+
+    #[test]
+    fn valid_scripts_parse() {
+        let ok = [
+            "cargo build --release && cargo test -- --nocapture 2>&1 | tee out.log",
+            "if [ -f x ]; then\n  echo yes\nelif true; then :\nelse\n  echo no\nfi",
+            "for f in *.rs; do\n  echo \"$f\"\ndone",
+            "while read -r line; do echo \"$line\"; done < input.txt",
+            "case \"$1\" in\n  a|b) echo ab ;;\n  (*) echo other ;;\nesac",
+            "cat <<'EOF' > out.txt\nif unbalanced\nEOF\necho after",
+            "greet() { echo \"hi $(whoami)\"; }\nfunction bye {\n  echo bye\n}",
+            "(cd sub && make) || exit 1",
+            "arr=(a b c); echo ${arr[@]} $((1 + 2)); ((i++))",
+            "diff <(sort a) <(sort b)",
+            "git push origin <branch>",
+            "cargo run --example <example name> -- <patch|minor|major>",
+            "sort <input.txt >sorted.txt",
+            "echo 'it''s' \"a \\\"quoted\\\" word\" # comment with ' quote",
+            "make \\\n  build",
+        ];
+        for script in ok {
+            assert!(parse(script).is_ok(), "{script}: {:?}", parse(script));
+        }
+    }
+
+    #[test]
+    fn syntax_errors_reported_with_line() {
+        assert_eq!(
+            error("echo hi\nif true; then\n  echo x\n"),
+            (2, "`if` without a matching `fi`".into())
+        );
+        assert_eq!(
+            error("echo 'oops\nmore"),
+            (1, "unterminated single quote".into())
+        );
+        assert_eq!(
+            error("echo \"oops"),
+            (1, "unterminated double quote".into())
+        );
+        assert_eq!(error("ls\ndone"), (2, "unexpected `done`".into()));
+        assert_eq!(error("&& ls"), (1, "unexpected `&&`".into()));
+        assert_eq!(error("ls |"), (1, "nothing after `|`".into()));
+        assert_eq!(error("echo >"), (1, "missing target after `>`".into()));
+        assert_eq!(
+            error("cat <<EOF\nbody"),
+            (1, "unterminated here-document (missing `EOF`)".into())
+        );
+        assert_eq!(
+            error("for x in a b\n  echo $x\ndone").1,
+            "unexpected `done`"
+        );
+    }
+
+    #[test]
+    fn argv_skips_assignments_and_wrappers() {
+        let cmds = parse("FOO=1 sudo -E rm -rf $DIR").unwrap();
+        let argv: Vec<&str> = cmds[0].argv().iter().map(|w| w.raw.as_str()).collect();
+        assert_eq!(argv, vec!["rm", "-rf", "$DIR"]);
+    }
+
+    #[test]
+    fn console_prompts_and_output() {
+        let content = "\
+```console
+$ echo \"one
+> two\"
+one
+two
+$ ls \\
+  -la
+total 0 (unbalanced ' output)
+```
+";
+        let block = &code_blocks(content)[0];
+        let script = shell_script(block).unwrap();
+        let lines: Vec<usize> = script.iter().map(|(n, _)| *n).collect();
+        assert_eq!(lines, vec![2, 3, 6, 7]);
+        assert!(parse_script(&script).is_ok());
+    }
+
+    #[test]
+    fn missing_scripts_and_risky_rm() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("scripts")).unwrap();
+        fs::write(tmp.path().join("scripts/setup.sh"), "").unwrap();
+        let content = "\
+```bash
+./scripts/setup.sh
+./scripts/missing.sh --flag
+bash tools/gen.sh
+rm -rf \"$BUILD_DIR\" $TMP/cache
+```
+
+```sh invalid
+if
+```
+
+```text
+if
+```
+";
+        let issues = check_shell("AGENTS.md", content, tmp.path());
+        let msgs: Vec<(usize, &str, bool)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str(), i.warning))
+            .collect();
+        assert_eq!(msgs.len(), 3, "{msgs:?}");
+        assert_eq!(
+            msgs[0],
+            (3, "Script `./scripts/missing.sh` not found", false)
+        );
+        assert_eq!(msgs[1], (4, "Script `tools/gen.sh` not found", false));
+        assert_eq!(msgs[2].0, 5);
+        assert!(
+            msgs[2]
+                .1
+                .starts_with("Unquoted expansion `$TMP/cache` in `rm`")
+        );
+        assert!(msgs[2].2);
+    }
+
+    #[test]
+    fn syntax_error_reported_at_file_line() {
+        let tmp = TempDir::new().unwrap();
+        let content = "# Setup\n\n```sh\nfor f in *; do\n  echo $f\n```\n";
+        let issues = check_shell("AGENTS.md", content, tmp.path());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 4);
+        assert_eq!(
+            issues[0].message,
+            "Shell syntax error: `for` without a matching `done`"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Skill-specific checks for `SKILL.md` files.
+//!
+//! A skill is a directory whose `SKILL.md` front matter tells the agent
+//! what the skill is (`name`, matching the directory) and when to load it
+//! (`description`). Files the skill bundles — scripts, references,
+//! runbooks — must live inside the skill directory, and every runbook in a
+//! `runbooks/` directory of the skill should be linked from `SKILL.md`.
+
+use crate::links::{extract_links, resolve};
+use crate::markdown::{code_spans, parse_front_matter, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Longest allowed `name`.
+pub const MAX_NAME_LEN: usize = 64;
+/// Longest allowed `description`.
+pub const MAX_DESCRIPTION_LEN: usize = 1024;
+/// Descriptions shorter than this rarely say when to use the skill.
+const MIN_DESCRIPTION_LEN: usize = 20;
+
+static NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9]+(?:-[a-z0-9]+)*$").unwrap());
+/// Code spans naming a file in a conventional skill subdirectory.
+static BUNDLED_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:\./)?(?:scripts|references|assets|templates|runbooks)/[\w./-]+$").unwrap()
+});
+
+/// Whether `rel` is a skill definition.
+pub fn is_skill_file(rel: &str) -> bool {
+    Path::new(rel).file_name().is_some_and(|n| n == "SKILL.md")
+}
+
+/// Validate a `SKILL.md` at `rel` (relative to `root`).
+pub fn check_skill(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let issue = |line: usize, message: String, warning: bool| Issue {
+        file: rel.to_string(),
+        line,
+        end_line: 0,
+        message,
+        warning,
+    };
+    let mut issues = Vec::new();
+    let skill_dir = Path::new(rel).parent().unwrap_or(Path::new(""));
+    let dir_name = skill_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match parse_front_matter(content) {
+        None => issues.push(issue(
+            1,
+            "SKILL.md has no front matter \u{2014} add `name` and `description`".to_string(),
+            false,
+        )),
+        Some(fm) => {
+            match fm.field("name") {
+                None => issues.push(issue(1, "SKILL.md front matter has no `name`".to_string(), false)),
+                Some(field) if !NAME_RE.is_match(&field.value) || field.value.len() > MAX_NAME_LEN => {
+                    issues.push(issue(
+                        field.line,
+                        format!(
+                            "Skill name `{}` must be lowercase letters, digits and hyphens (at most {} characters)",
+                            field.value, MAX_NAME_LEN
+                        ),
+                        false,
+                    ))
+                }
+                Some(field) if !dir_name.is_empty() && field.value != dir_name => {
+                    issues.push(issue(
+                        field.line,
+                        format!(
+                            "Skill name `{}` doesn't match its directory `{}`",
+                            field.value, dir_name
+                        ),
+                        false,
+                    ))
+                }
+                Some(_) => {}
+            }
+            match fm.field("description") {
+                None => issues.push(issue(
+                    1,
+                    "SKILL.md front matter has no `description`".to_string(),
+                    false,
+                )),
+                Some(field) => {
+                    let len = field.value.chars().count();
+                    if len > MAX_DESCRIPTION_LEN {
+                        issues.push(issue(
+                            field.line,
+                            format!(
+                                "Skill description is {} characters (max {})",
+                                len, MAX_DESCRIPTION_LEN
+                            ),
+                            false,
+                        ));
+                    } else if len < MIN_DESCRIPTION_LEN {
+                        issues.push(issue(
+                            field.line,
+                            "Skill description is too short to say when to use the skill"
+                                .to_string(),
+                            true,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Links that leave the skill directory; missing link targets are
+    // reported by `check_links`.
+    let mut linked = HashSet::new();
+    for link in extract_links(content) {
+        if link.is_absolute_url() || link.target.starts_with('#') {
+            continue;
+        }
+        let path = link.target.split('#').next().unwrap_or("");
+        if path.is_empty() {
+            continue;
+        }
+        let target = resolve(Path::new(rel), path);
+        if let Some(target) = &target {
+            linked.insert(target.clone());
+        }
+        if !target.is_some_and(|p| p.starts_with(skill_dir)) {
+            issues.push(issue(
+                link.line,
+                format!(
+                    "Link to `{}` leaves the skill directory \u{2014} bundle the file in {}/",
+                    link.target,
+                    skill_dir.display()
+                ),
+                false,
+            ));
+        }
+    }
+
+    for (line, text) in prose_lines(content) {
+        for span in code_spans(text) {
+            if BUNDLED_PATH_RE.is_match(&span) && !root.join(skill_dir).join(&span).exists() {
+                issues.push(issue(
+                    line,
+                    format!(
+                        "Bundled file `{}` not found in {}/",
+                        span,
+                        skill_dir.display()
+                    ),
+                    false,
+                ));
+            }
+        }
+    }
+
+    for runbook in skill_runbooks(&root.join(skill_dir)) {
+        if !linked.contains(&skill_dir.join(&runbook)) {
+            issues.push(issue(
+                0,
+                format!("Runbook `{}` isn't linked from SKILL.md", runbook.display()),
+                true,
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Markdown files under any `runbooks/` directory in a skill directory,
+/// relative to it and sorted.
+fn skill_runbooks(dir: &Path) -> Vec<PathBuf> {
+    fn walk(dir: &Path, in_runbooks: bool, out: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                let runbooks = in_runbooks || path.file_name().is_some_and(|n| n == "runbooks");
+                walk(&path, runbooks, out);
+            } else if in_runbooks && path.extension().is_some_and(|e| e == "md") {
+                out.push(path);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    walk(dir, false, &mut found);
+    let mut found: Vec<PathBuf> = found
+        .into_iter()
+        .filter_map(|p| p.strip_prefix(dir).ok().map(Path::to_path_buf))
+        .collect();
+    found.sort();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn bundled_skill_is_valid() {
+        let issues = check_skill(
+            ".claude/skills/instruction-files/SKILL.md",
+            crate::BUNDLED_SKILL,
+            Path::new(env!("CARGO_MANIFEST_DIR")),
+        );
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages.is_empty(), "{:?}", messages);
+    }
+
+    #[test]
+    fn front_matter() {
+        let rel = ".claude/skills/deploy/SKILL.md";
+        let root = Path::new("/nonexistent");
+        let messages = |content: &str| -> Vec<String> {
+            check_skill(rel, content, root)
+                .into_iter()
+                .map(|i| i.message)
+                .collect()
+        };
+        assert_eq!(
+            messages("# Deploy\n"),
+            vec!["SKILL.md has no front matter \u{2014} add `name` and `description`"]
+        );
+        assert_eq!(
+            messages("---\nname: release\n---\n"),
+            vec![
+                "Skill name `release` doesn't match its directory `deploy`",
+                "SKILL.md front matter has no `description`",
+            ]
+        );
+        assert_eq!(
+            messages("---\nname: Deploy_App\ndescription: Deploy.\n---\n"),
+            vec![
+                "Skill name `Deploy_App` must be lowercase letters, digits and hyphens (at most 64 characters)",
+                "Skill description is too short to say when to use the skill",
+            ]
+        );
+        let long = format!(
+            "---\nname: deploy\ndescription: {}\n---\n",
+            "x".repeat(1100)
+        );
+        assert_eq!(
+            messages(&long),
+            vec!["Skill description is 1100 characters (max 1024)"]
+        );
+    }
+
+    #[test]
+    fn bundled_files_and_runbooks() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let dir = root.join(".claude/skills/deploy");
+        fs::create_dir_all(dir.join("runbooks")).unwrap();
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(dir.join("scripts/deploy.sh"), "").unwrap();
+        fs::write(dir.join("runbooks/rollback.md"), "# Rollback\n").unwrap();
+        fs::write(dir.join("runbooks/release.md"), "# Release\n").unwrap();
+        fs::create_dir_all(dir.join("runbooks/ops")).unwrap();
+        fs::create_dir_all(dir.join("references/runbooks")).unwrap();
+        fs::write(dir.join("runbooks/ops/restart.md"), "# Restart\n").unwrap();
+        fs::write(dir.join("references/runbooks/cleanup.md"), "# Cleanup\n").unwrap();
+        let content = "\
+---
+name: deploy
+description: Deploy the service to staging or production.
+---
+Run `scripts/deploy.sh`, then `scripts/verify.sh`.
+See [release](runbooks/release.md) and [the guide](../../../docs/guide.md).
+Restart with [restart](./runbooks/ops/restart.md); runbooks/rollback.md is not a link.
+";
+        let issues = check_skill(".claude/skills/deploy/SKILL.md", content, root);
+        let found: Vec<(usize, &str, bool)> = issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str(), i.warning))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    6,
+                    "Link to `../../../docs/guide.md` leaves the skill directory \u{2014} bundle the file in .claude/skills/deploy/",
+                    false
+                ),
+                (
+                    5,
+                    "Bundled file `scripts/verify.sh` not found in .claude/skills/deploy/",
+                    false
+                ),
+                (
+                    0,
+                    "Runbook `references/runbooks/cleanup.md` isn't linked from SKILL.md",
+                    true
+                ),
+                (
+                    0,
+                    "Runbook `runbooks/rollback.md` isn't linked from SKILL.md",
+                    true
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! Quoted snippet validation for instruction files.
+//!
+//! A fenced code block can declare where it was copied from, either in the
+//! info string or in an HTML comment on the line before the fence:
+//!
+//! ````markdown
+//! ```rust title="src/lib.rs:40-60"
+//! ...
+//! ```
+//!
+//! <!-- source: src/lib.rs:40-60 -->
+//! ```rust
+//! ...
+//! ```
+//! ````
+//!
+//! The block must still match that region of the file, compared with
+//! whitespace normalized. [`fix_snippets`] refreshes drifted blocks from
+//! the source.
+
+use crate::links::resolve;
+use crate::markdown::{CodeBlock, code_blocks};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+static TITLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\btitle="([^"]+:\d+(?:-\d+)?)""#).unwrap());
+static COMMENT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*<!--\s*source:\s*(\S+)\s*-->\s*$").unwrap());
+static LOCATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(.+?)(?::(\d+)(?:-(\d+))?)?$").unwrap());
+
+/// A source location: file path plus optional 1-based inclusive line range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetSource {
+    pub path: String,
+    pub lines: Option<(usize, usize)>,
+}
+
+impl SnippetSource {
+    /// Parse `path`, `path:N` or `path:START-END`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let caps = LOCATION_RE.captures(s)?;
+        let path = caps[1].to_string();
+        let lines = match (caps.get(2), caps.get(3)) {
+            (Some(a), Some(b)) => Some((a.as_str().parse().ok()?, b.as_str().parse().ok()?)),
+            (Some(a), None) => {
+                let n = a.as_str().parse().ok()?;
+                Some((n, n))
+            }
+            _ => None,
+        };
+        Some(SnippetSource { path, lines })
+    }
+
+    /// Read the referenced region from disk. Sources must be inside `root`.
+    fn read(&self, root: &Path) -> Result<Vec<String>, String> {
+        let rel = if self.path.starts_with(['/', '~']) {
+            None
+        } else {
+            resolve(Path::new(""), &self.path)
+        }
+        .ok_or_else(|| format!("Snippet source `{}` is outside the project root", self.path))?;
+        let content = std::fs::read_to_string(root.join(rel))
+            .map_err(|_| format!("Snippet source `{}` not found", self.path))?;
+        let all: Vec<String> = content.lines().map(str::to_string).collect();
+        match self.lines {
+            None => Ok(all),
+            Some((start, end)) if start >= 1 && start <= end && end <= all.len() => {
+                Ok(all[start - 1..end].to_vec())
+            }
+            Some(_) => Err(format!(
+                "Snippet source `{}` is out of range ({} has {} lines)",
+                self,
+                self.path,
+                all.len()
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SnippetSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.lines {
+            Some((a, b)) if a == b => write!(f, "{}:{}", self.path, a),
+            Some((a, b)) => write!(f, "{}:{}-{}", self.path, a, b),
+            None => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Find the source annotation for a block: `title="path:lines"` in the info
+/// string, or a `<!-- source: path -->` comment on the line before the fence.
+fn block_source(block: &CodeBlock, all_lines: &[&str]) -> Option<SnippetSource> {
+    if let Some(caps) = TITLE_RE.captures(block.info) {
+        return SnippetSource::parse(&caps[1]);
+    }
+    let prev = all_lines.get(block.fence_line.checked_sub(2)?)?;
+    let caps = COMMENT_RE.captures(prev)?;
+    SnippetSource::parse(&caps[1])
+}
+
+/// Collapse whitespace runs and drop blank lines.
+fn normalize<S: AsRef<str>>(lines: &[S]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|l| l.as_ref().split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// Whether the block has a closing fence (unclosed blocks can't be fixed).
+///
+/// An unclosed block ends on its last body line, or on the opening fence
+/// itself when it has no body.
+fn is_closed(block: &CodeBlock) -> bool {
+    block.close_line > block.fence_line
+        && block
+            .lines
+            .last()
+            .is_none_or(|(n, _)| *n != block.close_line)
+}
+
+/// Annotated, closed blocks with their source and the current source text.
+fn annotated_blocks<'a>(
+    content: &'a str,
+    root: &Path,
+) -> Vec<(CodeBlock<'a>, SnippetSource, Result<Vec<String>, String>)> {
+    let all_lines: Vec<&str> = content.lines().collect();
+    code_blocks(content)
+        .into_iter()
+        .filter(is_closed)
+        .filter_map(|block| {
+            let source = block_source(&block, &all_lines)?;
+            let current = source.read(root);
+            Some((block, source, current))
+        })
+        .collect()
+}
+
+/// Check annotated code blocks against their source locations.
+///
+/// Missing files, sources outside `root`, out-of-range line numbers and
+/// drifted snippets are errors.
+pub fn check_snippets(rel: &str, content: &str, root: &Path) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (block, source, current) in annotated_blocks(content, root) {
+        let message = match current {
+            Err(message) => message,
+            Ok(current) => {
+                let quoted: Vec<&str> = block.lines.iter().map(|(_, l)| *l).collect();
+                if normalize(&quoted) == normalize(&current) {
+                    continue;
+                }
+                format!(
+                    "Snippet differs from `{}` \u{2014} run `audit --fix` to refresh",
+                    source
+                )
+            }
+        };
+        issues.push(Issue {
+            file: rel.to_string(),
+            line: block.fence_line,
+            end_line: block.close_line,
+            message,
+            warning: false,
+        });
+    }
+    issues
+}
+
+/// Refresh drifted snippets from their sources.
+///
+/// Returns the rewritten content and the number of blocks replaced. Blocks
+/// whose source is missing, outside `root` or out of range are left alone.
+pub fn fix_snippets(content: &str, root: &Path) -> (String, usize) {
+    let mut replacements = Vec::new();
+    for (block, _, current) in annotated_blocks(content, root) {
+        let Ok(current) = current else { continue };
+        let quoted: Vec<&str> = block.lines.iter().map(|(_, l)| *l).collect();
+        if normalize(&quoted) != normalize(&current) {
+            replacements.push((block.fence_line, block.close_line, current));
+        }
+    }
+    if replacements.is_empty() {
+        return (content.to_string(), 0);
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    for (fence, close, current) in &replacements {
+        out.extend(lines[i..*fence].iter().map(|l| l.to_string()));
+        out.extend(current.iter().cloned());
+        i = close - 1;
+    }
+    out.extend(lines[i..].iter().map(|l| l.to_string()));
+    let mut fixed = out.join("\n");
+    if content.ends_with('\n') {
+        fixed.push('\n');
+    }
+    (fixed, replacements.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/lib.rs"),
+            "// header\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        )
+        .unwrap();
+        tmp
+    }
+
+    #[test]
+    fn parse_locations() {
+        let s = SnippetSource::parse("src/lib.rs:40-60").unwrap();
+        assert_eq!(s.path, "src/lib.rs");
+        assert_eq!(s.lines, Some((40, 60)));
+        assert_eq!(SnippetSource::parse("a.rs:7").unwrap().lines, Some((7, 7)));
+        assert_eq!(SnippetSource::parse("a.rs").unwrap().lines, None);
+        assert_eq!(
+            SnippetSource::parse("src/lib.rs:40-60")
+                .unwrap()
+                .to_string(),
+            "src/lib.rs:40-60"
+        );
+    }
+
+    #[test]
+    fn matching_snippet_ignores_whitespace() {
+        let tmp = setup();
+        let content = "\
+```rust title=\"src/lib.rs:2-4\"
+pub fn add(a: i32,  b: i32) -> i32 {
+
+  a + b
+}
+```
+";
+        assert!(check_snippets("AGENTS.md", content, tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn drifted_snippet_and_bad_sources() {
+        let tmp = setup();
+        let content = "\
+<!-- source: src/lib.rs:2-4 -->
+```rust
+pub fn add(a: i32, b: i32) -> i32 {
+    a - b
+}
+```
+
+```rust title=\"src/gone.rs:1-2\"
+x
+```
+
+```rust title=\"src/lib.rs:3-9\"
+x
+```
+
+```rust title=\"Example\"
+not checked
+```
+
+```ini title=\"/home/u/.aws/credentials:1-5\"
+x
+```
+
+<!-- source: ../secrets.txt -->
+```
+x
+```
+";
+        let issues = check_snippets("AGENTS.md", content, tmp.path());
+        assert_eq!(issues.len(), 5);
+        assert_eq!((issues[0].line, issues[0].end_line), (2, 6));
+        assert!(
+            issues[0]
+                .message
+                .starts_with("Snippet differs from `src/lib.rs:2-4`")
+        );
+        assert_eq!(issues[1].message, "Snippet source `src/gone.rs` not found");
+        assert_eq!(
+            issues[2].message,
+            "Snippet source `src/lib.rs:3-9` is out of range (src/lib.rs has 4 lines)"
+        );
+        assert_eq!(
+            issues[3].message,
+            "Snippet source `/home/u/.aws/credentials` is outside the project root"
+        );
+        assert_eq!(
+            issues[4].message,
+            "Snippet source `../secrets.txt` is outside the project root"
+        );
+        assert!(issues.iter().all(|i| !i.warning));
+    }
+
+    #[test]
+    fn fix_refreshes_drifted_blocks() {
+        let tmp = setup();
+        let content = "\
+Intro
+```rust title=\"src/lib.rs:3\"
+    a - b
+```
+```rust title=\"src/lib.rs:1\"
+// header
+```
+End
+";
+        let (fixed, n) = fix_snippets(content, tmp.path());
+        assert_eq!(n, 1);
+        assert_eq!(
+            fixed,
+            "Intro\n```rust title=\"src/lib.rs:3\"\n    a + b\n```\n```rust title=\"src/lib.rs:1\"\n// header\n```\nEnd\n"
+        );
+        assert!(check_snippets("AGENTS.md", &fixed, tmp.path()).is_empty());
+        assert_eq!(fix_snippets(&fixed, tmp.path()).1, 0);
+    }
+
+    #[test]
+    fn unclosed_blocks_are_skipped() {
+        let tmp = setup();
+        for content in [
+            "```rust title=\"src/lib.rs:3\"\n    a - b\n",
+            "Intro\n```rust title=\"src/lib.rs:3\"",
+            "Intro\n```rust title=\"src/lib.rs:3\"\n",
+        ] {
+            assert!(check_snippets("AGENTS.md", content, tmp.path()).is_empty());
+            assert_eq!(fix_snippets(content, tmp.path()), (content.to_string(), 0));
+        }
+        let empty = "```rust title=\"src/lib.rs:3\"\n```\n";
+        assert_eq!(check_snippets("AGENTS.md", empty, tmp.path()).len(), 1);
+    }
+
+    #[test]
+    fn fix_never_copies_files_outside_root() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("project");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(tmp.path().join("secret.txt"), "token=abc\n").unwrap();
+        let content = "<!-- source: ../secret.txt -->\n```\nx\n```\n";
+        assert_eq!(fix_snippets(content, &root), (content.to_string(), 0));
+        let issues = check_snippets("AGENTS.md", content, &root);
+        assert_eq!(
+            issues[0].message,
+            "Snippet source `../secret.txt` is outside the project root"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Symbol reference validation for instruction files.
+//!
+//! Instruction files name functions and types in backticks
+//! (`` `find_instruction_files` ``, `` `AuditConfig::agent_doc()` ``).
+//! This module indexes every identifier in the project's source files and
+//! flags backticked names that no longer appear anywhere, with a
+//! "did you mean" suggestion. The index is cached on disk and only files
+//! whose modification time or size changed are rescanned.
+
+use crate::config::SymbolConfig;
+use crate::markdown::{code_spans, prose_lines};
+use crate::types::{AuditConfig, Issue};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Cache file location, relative to the project root.
+pub const INDEX_FILE: &str = ".instruction-files/symbol-index.tsv";
+
+static IDENT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+static SYMBOL_REF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*(?:(?:::|\.)[A-Za-z_][A-Za-z0-9_]*)*(?:\(\))?$").unwrap()
+});
+
+/// Cache key for a source file: modification time in nanoseconds and size.
+type Stamp = (u128, u64);
+
+/// Identifiers found in the project's source files.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    /// Source path (relative to root) -> (stamp, identifiers).
+    files: BTreeMap<PathBuf, (Stamp, BTreeSet<String>)>,
+    all: BTreeSet<String>,
+}
+
+impl SymbolIndex {
+    /// Build the index for `root`, reusing the on-disk cache for files whose
+    /// modification time and size are unchanged, and write the refreshed
+    /// cache back. The cache directory gets a `.gitignore` so it stays
+    /// untracked.
+    pub fn load(root: &Path, config: &AuditConfig) -> Self {
+        let cache_path = root.join(INDEX_FILE);
+        let cached = read_cache(&cache_path);
+        let mut index = SymbolIndex::default();
+        let mut dirty = false;
+
+        for path in source_files(root, config) {
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            let stamp = path.metadata().map_or((0, 0), |m| {
+                let mtime = m
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_nanos());
+                (mtime, m.len())
+            });
+            let idents = match cached.get(&rel) {
+                Some((s, idents)) if *s == stamp => idents.clone(),
+                _ => {
+                    dirty = true;
+                    let content = std::fs::read_to_string(&path).unwrap_or_default();
+                    IDENT_RE
+                        .find_iter(&content)
+                        .map(|m| m.as_str().to_string())
+                        .collect()
+                }
+            };
+            index.files.insert(rel, (stamp, idents));
+        }
+        if dirty || cached.len() != index.files.len() {
+            write_cache(&cache_path, &index.files);
+        }
+        index.all = index
+            .files
+            .values()
+            .flat_map(|(_, i)| i.iter().cloned())
+            .collect();
+        index
+    }
+
+    /// Build an index from in-memory identifiers (no caching).
+    pub fn from_identifiers<I: IntoIterator<Item = S>, S: Into<String>>(idents: I) -> Self {
+        SymbolIndex {
+            files: BTreeMap::new(),
+            all: idents.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all.is_empty()
+    }
+
+    pub fn contains(&self, ident: &str) -> bool {
+        self.all.contains(ident)
+    }
+
+    /// Closest indexed identifier to `ident`, if any is similar enough.
+    pub fn suggest(&self, ident: &str) -> Option<&str> {
+        self.all
+            .iter()
+            .map(|c| (strsim::normalized_damerau_levenshtein(ident, c), c))
+            .filter(|(score, _)| *score >= 0.75)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, c)| c.as_str())
+    }
+}
+
+fn source_files(root: &Path, config: &AuditConfig) -> Vec<PathBuf> {
+    fn walk(dir: &Path, config: &AuditConfig, out: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if !config.skip_dirs.contains(&name) {
+                    walk(&path, config, out);
+                }
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| config.source_extensions.contains(&e))
+            {
+                out.push(path);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    for dir in &config.source_dirs {
+        walk(&root.join(dir), config, &mut out);
+    }
+    out.sort();
+    out
+}
+
+fn read_cache(path: &Path) -> BTreeMap<PathBuf, (Stamp, BTreeSet<String>)> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, '\t');
+            let file = PathBuf::from(parts.next()?);
+            let mtime = parts.next()?.parse().ok()?;
+            let size = parts.next()?.parse().ok()?;
+            let idents = parts
+                .next()
+                .unwrap_or("")
+                .split(' ')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect();
+            Some((file, ((mtime, size), idents)))
+        })
+        .collect()
+}
+
+fn write_cache(path: &Path, files: &BTreeMap<PathBuf, (Stamp, BTreeSet<String>)>) {
+    let mut out = String::new();
+    for (file, ((mtime, size), idents)) in files {
+        let idents: Vec<&str> = idents.iter().map(String::as_str).collect();
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            file.to_string_lossy(),
+            mtime,
+            size,
+            idents.join(" ")
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+        let ignore = parent.join(".gitignore");
+        if !ignore.exists() {
+            let _ = std::fs::write(ignore, "*\n");
+        }
+    }
+    let _ = std::fs::write(path, out);
+}
+
+/// Whether an identifier segment looks like a code symbol rather than a
+/// plain word: `snake_case` with an underscore, or `CamelCase` with an
+/// inner capital. ALL_CAPS names are skipped (usually env vars).
+fn looks_like_symbol(ident: &str) -> bool {
+    let has_lower = ident.chars().any(|c| c.is_ascii_lowercase());
+    if !has_lower {
+        return false;
+    }
+    let snake = ident.trim_matches('_').contains('_');
+    let camel = ident
+        .chars()
+        .zip(ident.chars().skip(1))
+        .any(|(a, b)| a.is_ascii_lowercase() && b.is_ascii_uppercase());
+    snake || camel
+}
+
+/// Extract backticked symbol references: `(line_number, reference, segments)`.
+///
+/// A span qualifies when it is a (possibly `::`/`.`-qualified) identifier
+/// path, optionally ending in `()`. Only segments that look like code
+/// symbols are returned for lookup.
+pub fn extract_symbol_refs(content: &str) -> Vec<(usize, String, Vec<String>)> {
+    let mut refs = Vec::new();
+    for (line, text) in prose_lines(content) {
+        for span in code_spans(text) {
+            if !SYMBOL_REF_RE.is_match(&span) {
+                continue;
+            }
+            let segments: Vec<String> = span
+                .trim_end_matches("()")
+                .split("::")
+                .flat_map(|s| s.split('.'))
+                .filter(|s| looks_like_symbol(s))
+                .map(str::to_string)
+                .collect();
+            if !segments.is_empty() {
+                refs.push((line, span, segments));
+            }
+        }
+    }
+    refs
+}
+
+/// Check backticked symbol references against the source index.
+///
+/// Returns nothing when the index is empty (no source directories).
+/// Unknown symbols are warnings, with the closest indexed identifier as a
+/// suggestion when one is similar enough.
+pub fn check_symbols(
+    rel: &str,
+    content: &str,
+    index: &SymbolIndex,
+    config: &SymbolConfig,
+) -> Vec<Issue> {
+    if index.is_empty() {
+        return Vec::new();
+    }
+    let mut issues = Vec::new();
+    for (line, reference, segments) in extract_symbol_refs(content) {
+        for seg in segments {
+            if index.contains(&seg) || config.ignore.contains(&seg) {
+                continue;
+            }
+            let mut message = format!("Unknown symbol `{}` in `{}`", seg, reference);
+            if let Some(s) = index.suggest(&seg) {
+                message.push_str(&format!(" \u{2014} did you mean `{}`?", s));
+            }
+            issues.push(Issue {
+                file: rel.to_string(),
+                line,
+                end_line: 0,
+                message,
+                warning: true,
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn extract_symbol_refs_filters_plain_words() {
+        let content = "\
+Call `find_instruction_files` or `AuditConfig::agent_doc()`.
+Not `cargo test`, `Issue`, `src/lib.rs`, `RUST_LOG` or `true`.
+";
+        let refs = extract_symbol_refs(content);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].2, vec!["find_instruction_files"]);
+        assert_eq!(refs[1].1, "AuditConfig::agent_doc()");
+        assert_eq!(refs[1].2, vec!["AuditConfig", "agent_doc"]);
+    }
+
+    #[test]
+    fn unknown_symbol_with_suggestion() {
+        let index = SymbolIndex::from_identifiers(["find_instruction_files", "AuditConfig"]);
+        let content = "Use `find_instruction_file` and `AuditConfig`.\nThen `totally_gone()`.\n";
+        let issues = check_symbols("AGENTS.md", content, &index, &SymbolConfig::default());
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0].message,
+            "Unknown symbol `find_instruction_file` in `find_instruction_file` \u{2014} did you mean `find_instruction_files`?"
+        );
+        assert_eq!(issues[1].line, 2);
+        assert!(!issues[1].message.contains("did you mean"));
+        assert!(issues.iter().all(|i| i.warning));
+    }
+
+    #[test]
+    fn ignore_list_suppresses() {
+        let index = SymbolIndex::from_identifiers(["main"]);
+        let config = SymbolConfig {
+            ignore: vec!["source_extensions".to_string()],
+        };
+        let issues = check_symbols("AGENTS.md", "`source_extensions`\n", &index, &config);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn index_built_from_source_dirs_and_cached() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src/target")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn check_symbols() {}\n").unwrap();
+        fs::write(root.join("src/notes.txt"), "fn not_indexed() {}\n").unwrap();
+
+        let config = AuditConfig::corky();
+        let index = SymbolIndex::load(root, &config);
+        assert!(index.contains("check_symbols"));
+        assert!(!index.contains("not_indexed"));
+        let cache = fs::read_to_string(root.join(INDEX_FILE)).unwrap();
+        assert!(cache.starts_with("src/lib.rs\t"));
+
+        assert_eq!(
+            fs::read_to_string(root.join(".instruction-files/.gitignore")).unwrap(),
+            "*\n"
+        );
+
+        // A cached entry with a matching mtime and size is trusted without
+        // rescanning.
+        let tampered = cache.replace("check_symbols", "from_cache_only");
+        fs::write(root.join(INDEX_FILE), tampered).unwrap();
+        let index = SymbolIndex::load(root, &config);
+        assert!(index.contains("from_cache_only"));
+
+        // Same mtime, different size: rescanned.
+        let lib = root.join("src/lib.rs");
+        let mtime = fs::metadata(&lib).unwrap().modified().unwrap();
+        fs::write(&lib, "pub fn check_symbols_v2() {}\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&lib)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let index = SymbolIndex::load(root, &config);
+        assert!(index.contains("check_symbols_v2"));
+        assert!(!index.contains("from_cache_only"));
+    }
+}
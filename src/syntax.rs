@@ -0,0 +1,231 @@
+//! Syntax validation for configuration code blocks.
+//!
+//! Fenced blocks tagged `json`, `jsonc`, `toml` or `yaml`/`yml` are parsed and
+//! parse errors are reported at the line in the instruction file where they
+//! occur. Add `invalid` to the info string (```` ```json invalid ````) for
+//! blocks that intentionally show broken content.
+
+use crate::markdown::{CodeBlock, code_blocks};
+use crate::types::Issue;
+
+/// Info string attribute that opts a block out of syntax checking.
+pub const OPT_OUT: &str = "invalid";
+
+/// Replace `//` and `/* */` comments and trailing commas with spaces,
+/// keeping line and column positions intact.
+fn strip_jsonc(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = chars.clone();
+    let mut i = 0;
+    let mut in_string = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            if c == '\\' {
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                out[i] = ' ';
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            let end = (i + 2).min(chars.len());
+            for slot in &mut out[start..end] {
+                if *slot != '\n' {
+                    *slot = ' ';
+                }
+            }
+            i = end;
+            continue;
+        }
+        i += 1;
+    }
+
+    // Trailing commas, now that comments are blanked out.
+    in_string = false;
+    let mut i = 0;
+    while i < out.len() {
+        let c = out[i];
+        if in_string {
+            if c == '\\' {
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = out[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                out[i] = ' ';
+            }
+        }
+        i += 1;
+    }
+    out.into_iter().collect()
+}
+
+/// 1-based line within `text` for a byte offset.
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// Parse `text` as `lang`, returning `(block_line, message)` on failure.
+fn parse_error(lang: &str, text: &str) -> Option<(usize, String)> {
+    match lang {
+        "json" | "jsonc" => {
+            let source = if lang == "jsonc" {
+                strip_jsonc(text)
+            } else {
+                text.to_string()
+            };
+            let err = serde_json::from_str::<serde_json::Value>(&source).err()?;
+            let msg = err.to_string();
+            let msg = msg
+                .rsplit_once(" at line ")
+                .map_or(msg.as_str(), |(m, _)| m);
+            Some((err.line(), format!("{} (column {})", msg, err.column())))
+        }
+        "toml" => {
+            let err = toml::from_str::<toml::Table>(text).err()?;
+            let line = err.span().map_or(1, |s| line_at(text, s.start));
+            Some((line, err.message().to_string()))
+        }
+        "yaml" | "yml" => {
+            let err = yaml_rust2::YamlLoader::load_from_str(text).err()?;
+            let marker = err.marker();
+            Some((
+                marker.line(),
+                format!("{} (column {})", err.info(), marker.col() + 1),
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn display_lang(lang: &str) -> &str {
+    match lang {
+        "json" => "JSON",
+        "jsonc" => "JSONC",
+        "toml" => "TOML",
+        _ => "YAML",
+    }
+}
+
+/// Map a 1-based line inside the block to a line in the instruction file.
+fn file_line(block: &CodeBlock, block_line: usize) -> usize {
+    block
+        .lines
+        .get(block_line.saturating_sub(1))
+        .map_or(block.close_line, |(n, _)| *n)
+}
+
+/// Parse JSON, JSONC, TOML and YAML code blocks and report syntax errors.
+pub fn check_code_syntax(rel: &str, content: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for block in code_blocks(content) {
+        if block.info.split_whitespace().any(|w| w == OPT_OUT) || block.lines.is_empty() {
+            continue;
+        }
+        let lang = block.lang();
+        let text: Vec<&str> = block.lines.iter().map(|(_, l)| *l).collect();
+        let Some((line, message)) = parse_error(&lang, &text.join("\n")) else {
+            continue;
+        };
+        issues.push(Issue {
+            file: rel.to_string(),
+            line: file_line(&block, line),
+            end_line: 0,
+            message: format!("Invalid {} in code block: {}", display_lang(&lang), message),
+            warning: false,
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_blocks_pass() {
+        let content = "\
+```json
+{\"a\": [1, 2]}
+```
+```jsonc
+{
+  // comment, with a comma
+  \"a\": \"/* not a comment */\", /* block */
+  \"b\": [1, 2,],
+}
+```
+```toml
+[package]
+name = \"x\"
+```
+```yaml
+key:
+  - one
+  - two
+```
+```rust
+fn not_checked( {
+```
+";
+        assert!(check_code_syntax("AGENTS.md", content).is_empty());
+    }
+
+    #[test]
+    fn errors_point_at_instruction_file_line() {
+        let content = "\
+# Config
+
+```json
+{
+  \"a\": 1,
+}
+```
+
+```toml
+[package]
+name = \"x
+```
+
+```yaml
+key: [1, 2
+other: 3
+```
+";
+        let issues = check_code_syntax("AGENTS.md", content);
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].line, 6);
+        assert!(
+            issues[0]
+                .message
+                .starts_with("Invalid JSON in code block: trailing comma")
+        );
+        assert_eq!(issues[1].line, 11);
+        assert!(issues[1].message.starts_with("Invalid TOML in code block:"));
+        assert!(issues[2].message.starts_with("Invalid YAML in code block:"));
+        assert_eq!(issues[2].line, 16);
+        assert!(issues.iter().all(|i| !i.warning));
+    }
+
+    #[test]
+    fn opt_out_attribute_skips_block() {
+        let content = "```json invalid\n{\"a\": 1,}\n```\n";
+        assert!(check_code_syntax("AGENTS.md", content).is_empty());
+    }
+}
@@ -0,0 +1,376 @@
+//! External URL checking for instruction files (`audit --check-urls`).
+//!
+//! Behind the `check-urls` feature gate. Sends a HEAD request to every
+//! http(s) link (falling back to GET when HEAD is refused), with a bounded
+//! worker pool and a per-host delay. Results are cached on disk under the
+//! project so repeated runs only hit the network once per TTL.
+
+use crate::config::UrlConfig;
+use crate::links::extract_links;
+use crate::markdown::{mask_code_spans, prose_lines};
+use crate::types::Issue;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Cache file location, relative to the project root.
+pub const CACHE_FILE: &str = ".instruction-files/url-cache.tsv";
+
+static BARE_URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).unwrap());
+
+/// Result of probing a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// HTTP status of the final response (after redirects).
+    Status(u16),
+    /// Connection, DNS or TLS failure.
+    Error(String),
+}
+
+/// Extract http(s) URLs from markdown links and bare URLs in prose.
+///
+/// Returns `(line_number, url)` pairs, deduplicated per line. URLs in code
+/// blocks and inline code are skipped.
+pub fn extract_urls(content: &str) -> Vec<(usize, String)> {
+    let mut urls: Vec<(usize, String)> = extract_links(content)
+        .into_iter()
+        .filter(|l| l.target.starts_with("http://") || l.target.starts_with("https://"))
+        .map(|l| (l.line, l.target))
+        .collect();
+    for (line, text) in prose_lines(content) {
+        for m in BARE_URL_RE.find_iter(&mask_code_spans(text)) {
+            let url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            if !urls.iter().any(|(l, u)| *l == line && u == url) {
+                urls.push((line, url.to_string()));
+            }
+        }
+    }
+    urls.sort();
+    urls
+}
+
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    host.split(':').next().unwrap_or("")
+}
+
+/// Match a URL against an allow/deny pattern: a host (`docs.rs`), a
+/// wildcard host (`*.github.com`, also matching `github.com`), or a URL prefix.
+fn matches_pattern(pattern: &str, url: &str) -> bool {
+    if pattern.contains("://") {
+        return url.starts_with(pattern);
+    }
+    let host = host_of(url).to_ascii_lowercase();
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host == suffix || host.ends_with(&format!(".{}", suffix)),
+        None => host == pattern,
+    }
+}
+
+fn is_selected(url: &str, config: &UrlConfig) -> bool {
+    if config.deny.iter().any(|p| matches_pattern(p, url)) {
+        return false;
+    }
+    config.allow.is_empty() || config.allow.iter().any(|p| matches_pattern(p, url))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Load `url -> (status, checked_at)` entries from the TSV cache.
+fn load_cache(path: &Path) -> BTreeMap<String, (u16, u64)> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let url = parts.next()?;
+            let status = parts.next()?.parse().ok()?;
+            let at = parts.next()?.parse().ok()?;
+            Some((url.to_string(), (status, at)))
+        })
+        .collect()
+}
+
+fn save_cache(path: &Path, cache: &BTreeMap<String, (u16, u64)>) {
+    let mut out = String::new();
+    for (url, (status, at)) in cache {
+        out.push_str(&format!("{}\t{}\t{}\n", url, status, at));
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(path, out);
+}
+
+fn probe(agent: &ureq::Agent, url: &str) -> Outcome {
+    let head = match agent.head(url).call() {
+        Ok(resp) => Outcome::Status(resp.status()),
+        Err(ureq::Error::Status(code, _)) => Outcome::Status(code),
+        Err(ureq::Error::Transport(t)) => Outcome::Error(t.to_string()),
+    };
+    // Some servers refuse HEAD outright; retry those with GET.
+    match head {
+        Outcome::Status(403 | 405 | 501) | Outcome::Error(_) => match agent.get(url).call() {
+            Ok(resp) => Outcome::Status(resp.status()),
+            Err(ureq::Error::Status(code, _)) => Outcome::Status(code),
+            Err(ureq::Error::Transport(t)) => Outcome::Error(t.to_string()),
+        },
+        other => other,
+    }
+}
+
+/// Probe `urls` with at most `config.concurrency` requests in flight and
+/// at least `config.per_domain_delay_ms` between requests to one host.
+fn probe_all(urls: Vec<String>, config: &UrlConfig) -> HashMap<String, Outcome> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .redirects(5)
+        .build();
+    let delay = Duration::from_millis(config.per_domain_delay_ms);
+    let queue = Mutex::new(urls);
+    let next_slot: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+    let results = Mutex::new(HashMap::new());
+
+    std::thread::scope(|s| {
+        for _ in 0..config.concurrency.max(1) {
+            s.spawn(|| {
+                loop {
+                    let Some(url) = queue.lock().unwrap().pop() else {
+                        break;
+                    };
+                    let wait = {
+                        let mut slots = next_slot.lock().unwrap();
+                        let now = Instant::now();
+                        let at = slots.get(host_of(&url)).copied().unwrap_or(now).max(now);
+                        slots.insert(host_of(&url).to_string(), at + delay);
+                        at - now
+                    };
+                    std::thread::sleep(wait);
+                    let outcome = probe(&agent, &url);
+                    results.lock().unwrap().insert(url, outcome);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+/// Check external http(s) links in `files`.
+///
+/// Each distinct URL is requested once per run. Results with an HTTP
+/// status are cached in [`CACHE_FILE`] for `config.cache_ttl_hours`;
+/// connection failures are always retried. Dead links (status >= 400) and
+/// unreachable hosts are reported as warnings on every line they appear.
+pub fn check_urls(files: &[PathBuf], root: &Path, config: &UrlConfig) -> Vec<Issue> {
+    let mut occurrences: Vec<(String, usize, String)> = Vec::new();
+    for f in files {
+        if let Ok(content) = std::fs::read_to_string(f) {
+            let rel = f
+                .strip_prefix(root)
+                .unwrap_or(f)
+                .to_string_lossy()
+                .to_string();
+            for (line, url) in extract_urls(&content) {
+                if is_selected(&url, config) {
+                    occurrences.push((rel.clone(), line, url));
+                }
+            }
+        }
+    }
+    if occurrences.is_empty() {
+        return Vec::new();
+    }
+
+    let cache_path = root.join(CACHE_FILE);
+    let mut cache = load_cache(&cache_path);
+    let now = now_secs();
+    let ttl = config.cache_ttl_hours * 3600;
+    cache.retain(|_, (_, at)| now.saturating_sub(*at) < ttl);
+
+    let mut pending: Vec<String> = occurrences
+        .iter()
+        .map(|(_, _, url)| url.clone())
+        .filter(|url| !cache.contains_key(url))
+        .collect();
+    pending.sort();
+    pending.dedup();
+
+    let mut outcomes: HashMap<String, Outcome> = cache
+        .iter()
+        .map(|(url, (status, _))| (url.clone(), Outcome::Status(*status)))
+        .collect();
+    for (url, outcome) in probe_all(pending, config) {
+        if let Outcome::Status(code) = outcome {
+            cache.insert(url.clone(), (code, now));
+        }
+        outcomes.insert(url, outcome);
+    }
+    save_cache(&cache_path, &cache);
+
+    let mut issues = Vec::new();
+    for (file, line, url) in occurrences {
+        let message = match outcomes.get(&url) {
+            Some(Outcome::Status(code)) if *code >= 400 => {
+                format!("Dead link {} \u{2014} HTTP {}", url, code)
+            }
+            Some(Outcome::Error(err)) => format!("Unreachable link {} \u{2014} {}", url, err),
+            _ => continue,
+        };
+        issues.push(Issue {
+            file,
+            line,
+            end_line: 0,
+            message,
+            warning: true,
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Minimal HTTP server: `/ok` -> 200, `/gone` -> 404,
+    /// `/nohead` -> 405 for HEAD and 200 for GET. Returns base URL and a
+    /// request counter.
+    fn serve() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or("");
+                let path = parts.next().unwrap_or("");
+                let status = match (method, path) {
+                    (_, "/ok") => "200 OK",
+                    ("HEAD", "/nohead") => "405 Method Not Allowed",
+                    ("GET", "/nohead") => "200 OK",
+                    _ => "404 Not Found",
+                };
+                let mut stream = stream;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+            }
+        });
+        (base, hits)
+    }
+
+    fn quick_config() -> UrlConfig {
+        UrlConfig {
+            per_domain_delay_ms: 0,
+            timeout_secs: 5,
+            ..UrlConfig::default()
+        }
+    }
+
+    #[test]
+    fn extract_urls_links_and_bare() {
+        let content = "\
+See [docs](https://docs.rs/regex) or https://example.com/page.
+`https://in-code.example`
+[local](docs/a.md)
+";
+        let urls = extract_urls(content);
+        assert_eq!(
+            urls,
+            vec![
+                (1, "https://docs.rs/regex".to_string()),
+                (1, "https://example.com/page".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_and_deny_patterns() {
+        let config = UrlConfig {
+            allow: vec!["*.github.com".to_string(), "https://docs.rs/".to_string()],
+            deny: vec!["gist.github.com".to_string()],
+            ..UrlConfig::default()
+        };
+        assert!(is_selected("https://github.com/btakita", &config));
+        assert!(is_selected("https://api.github.com/repos", &config));
+        assert!(is_selected("https://docs.rs/regex", &config));
+        assert!(!is_selected("https://gist.github.com/x", &config));
+        assert!(!is_selected("https://example.com/", &config));
+    }
+
+    #[test]
+    fn dead_links_reported_with_status() {
+        let (base, _) = serve();
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("AGENTS.md"),
+            format!(
+                "[ok]({b}/ok)\n[gone]({b}/gone)\n[nohead]({b}/nohead)\n",
+                b = base
+            ),
+        )
+        .unwrap();
+
+        let issues = check_urls(&[root.join("AGENTS.md")], root, &quick_config());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 2);
+        assert!(issues[0].warning);
+        assert!(issues[0].message.contains("HTTP 404"));
+    }
+
+    #[test]
+    fn results_are_cached_between_runs() {
+        let (base, hits) = serve();
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let doc = root.join("AGENTS.md");
+        fs::write(&doc, format!("{b}/ok and {b}/gone\n", b = base)).unwrap();
+
+        let first = check_urls(std::slice::from_ref(&doc), root, &quick_config());
+        let after_first = hits.load(Ordering::SeqCst);
+        let second = check_urls(std::slice::from_ref(&doc), root, &quick_config());
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        assert_eq!(hits.load(Ordering::SeqCst), after_first);
+        assert!(root.join(CACHE_FILE).exists());
+
+        let expired = UrlConfig {
+            cache_ttl_hours: 0,
+            ..quick_config()
+        };
+        check_urls(&[doc], root, &expired);
+        assert!(hits.load(Ordering::SeqCst) > after_first);
+    }
+}